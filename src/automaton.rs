@@ -0,0 +1,231 @@
+//! Cellular automata running on a [`Grid`].
+
+use crate::grid::{Grid, Point};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// How the cells of an automaton evolve.
+pub trait Rule {
+    type Cell: Clone + PartialEq;
+
+    /// Points of the cells deciding the next state of the cell at `point`.
+    ///
+    /// It is computed once, on the initial grid, so it must not depend on
+    /// cells that change. It must also be symmetric: when a cell changes,
+    /// only the cells in its own neighbourhood are evaluated again.
+    fn neighbourhood(&self, grid: &Grid<Self::Cell>, point: Point) -> Vec<Point>;
+
+    /// Next state of `cell`, given the current state of its neighbourhood.
+    fn transition<'a>(
+        &self,
+        cell: &Self::Cell,
+        neighbours: impl Iterator<Item = &'a Self::Cell>,
+    ) -> Self::Cell
+    where
+        Self::Cell: 'a;
+}
+
+/// Generations between the first occurrence of a repeated state and its return.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    /// Generation at which the cycle starts.
+    pub start: usize,
+    /// 1 for a fixpoint.
+    pub period: usize,
+}
+
+pub struct Automaton<R: Rule> {
+    rule: R,
+    current: Grid<R::Cell>,
+    next: Grid<R::Cell>,
+    neighbourhoods: Vec<Vec<usize>>,
+    frontier: Vec<usize>,
+    in_frontier: Vec<bool>,
+    generation: usize,
+}
+
+impl<R: Rule> Automaton<R> {
+    pub fn new(grid: Grid<R::Cell>, rule: R) -> Self {
+        let neighbourhoods = grid
+            .iter()
+            .map(|(point, _)| {
+                rule.neighbourhood(&grid, point)
+                    .into_iter()
+                    .filter_map(|neighbour| grid.index_of(neighbour))
+                    .collect()
+            })
+            .collect();
+        let len = grid.cells().len();
+
+        Automaton {
+            rule,
+            next: grid.clone(),
+            current: grid,
+            neighbourhoods,
+            frontier: (0..len).collect(),
+            in_frontier: vec![true; len],
+            generation: 0,
+        }
+    }
+
+    pub fn grid(&self) -> &Grid<R::Cell> {
+        &self.current
+    }
+
+    pub fn into_grid(self) -> Grid<R::Cell> {
+        self.current
+    }
+
+    /// Number of steps run so far.
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Advances by one generation, evaluating only the cells next to the
+    /// previous changes. Returns how many cells changed.
+    pub fn step(&mut self) -> usize {
+        let cells = self.current.cells();
+        let mut changed = vec![];
+
+        for &i in &self.frontier {
+            let neighbours = self.neighbourhoods[i].iter().map(|&j| &cells[j]);
+            let cell = self.rule.transition(&cells[i], neighbours);
+            if cell != cells[i] {
+                self.next.cells_mut()[i] = cell;
+                changed.push(i);
+            }
+        }
+
+        std::mem::swap(&mut self.current, &mut self.next);
+        for &i in &self.frontier {
+            self.in_frontier[i] = false;
+        }
+        self.frontier.clear();
+        for &i in &changed {
+            self.next.cells_mut()[i] = self.current.cells()[i].clone();
+            for &j in std::iter::once(&i).chain(&self.neighbourhoods[i]) {
+                if !self.in_frontier[j] {
+                    self.in_frontier[j] = true;
+                    self.frontier.push(j);
+                }
+            }
+        }
+
+        self.generation += 1;
+        changed.len()
+    }
+
+    pub fn run(&mut self, steps: usize) {
+        for _ in 0..steps {
+            self.step();
+        }
+    }
+
+    /// Steps until nothing changes anymore, which never happens if the
+    /// automaton oscillates. Returns the generation of the fixpoint.
+    pub fn run_to_fixpoint(&mut self) -> usize {
+        while self.step() > 0 {}
+        self.generation - 1
+    }
+}
+
+impl<R: Rule> Automaton<R>
+where
+    R::Cell: Hash,
+{
+    /// Steps until a state seen before comes back.
+    pub fn run_until_repeat(&mut self) -> Cycle {
+        self.run_until_repeat_observing(|_| ()).0
+    }
+
+    /// Steps until a state seen before comes back, also returning what
+    /// `observe` tells of every generation up to the repeated one, excluded.
+    ///
+    /// Only a hash of each generation is kept, so telling two states apart
+    /// relies on their hashes being different.
+    pub fn run_until_repeat_observing<T>(
+        &mut self,
+        mut observe: impl FnMut(&Grid<R::Cell>) -> T,
+    ) -> (Cycle, Vec<T>) {
+        let mut seen = HashMap::new();
+        let mut observed = vec![];
+        loop {
+            let mut hasher = DefaultHasher::new();
+            self.current.hash(&mut hasher);
+            let hash = hasher.finish();
+            if let Some(&start) = seen.get(&hash) {
+                let cycle = Cycle {
+                    start,
+                    period: self.generation - start,
+                };
+                return (cycle, observed);
+            }
+            seen.insert(hash, self.generation);
+            observed.push(observe(&self.current));
+            self.step();
+        }
+    }
+}
+
+#[cfg(test)]
+struct Life;
+
+#[cfg(test)]
+impl Rule for Life {
+    type Cell = bool;
+
+    fn neighbourhood(&self, grid: &Grid<bool>, point: Point) -> Vec<Point> {
+        grid.neighbours8(point).map(|(p, _)| p).collect()
+    }
+
+    fn transition<'a>(&self, &alive: &bool, neighbours: impl Iterator<Item = &'a bool>) -> bool {
+        let alive_neighbours = neighbours.filter(|&&n| n).count();
+        alive_neighbours == 3 || (alive && alive_neighbours == 2)
+    }
+}
+
+#[cfg(test)]
+fn life(input: &str) -> Grid<bool> {
+    Grid::parse(0, input, |c| Ok(c == '#')).unwrap()
+}
+
+#[test]
+fn test_blinker() {
+    let vertical = life(".....\n..#..\n..#..\n..#..\n.....");
+    let horizontal = life(".....\n.....\n.###.\n.....\n.....");
+
+    let mut automaton = Automaton::new(vertical.clone(), Life);
+    assert_eq!(automaton.step(), 4);
+    assert_eq!(automaton.grid(), &horizontal);
+    automaton.run(3);
+    assert_eq!(automaton.grid(), &vertical);
+    assert_eq!(automaton.generation(), 4);
+
+    let mut automaton = Automaton::new(vertical, Life);
+    let alive = |grid: &Grid<bool>| grid.cells().iter().filter(|&&c| c).count();
+    assert_eq!(
+        automaton.run_until_repeat_observing(alive),
+        (
+            Cycle {
+                start: 0,
+                period: 2
+            },
+            vec![3, 3]
+        )
+    );
+}
+
+#[test]
+fn test_fixpoint() {
+    let mut automaton = Automaton::new(life("#...\n##..\n....\n...#"), Life);
+    assert_eq!(automaton.run_to_fixpoint(), 1);
+    assert_eq!(automaton.grid(), &life("##..\n##..\n....\n...."));
+    assert_eq!(
+        automaton.run_until_repeat(),
+        Cycle {
+            start: 2,
+            period: 1
+        }
+    );
+}
@@ -0,0 +1,145 @@
+use crate::solution::{Day, Part};
+use crate::Result;
+use serde::{Serialize, Serializer};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// How many times each step is run: at least `min_runs`, then until either
+/// `max_runs` or the `budget` of time is reached.
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    pub min_runs: usize,
+    pub max_runs: usize,
+    pub budget: Duration,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            min_runs: 1,
+            max_runs: 1000,
+            budget: Duration::from_secs(1),
+        }
+    }
+}
+
+impl Options {
+    /// Exactly `runs` runs, at least one.
+    pub fn runs(runs: usize) -> Self {
+        let runs = runs.max(1);
+        Options {
+            min_runs: runs,
+            max_runs: runs,
+            budget: Duration::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Stats {
+    pub runs: usize,
+    #[serde(rename = "mean_ns", serialize_with = "as_nanos")]
+    pub mean: Duration,
+    #[serde(rename = "stddev_ns", serialize_with = "as_nanos")]
+    pub stddev: Duration,
+    #[serde(rename = "min_ns", serialize_with = "as_nanos")]
+    pub min: Duration,
+    #[serde(rename = "max_ns", serialize_with = "as_nanos")]
+    pub max: Duration,
+}
+
+impl Stats {
+    fn from_samples(samples: &[Duration]) -> Self {
+        let runs = samples.len();
+        let mean = samples.iter().sum::<Duration>() / runs as u32;
+        let variance = samples
+            .iter()
+            .map(|s| (s.as_secs_f64() - mean.as_secs_f64()).powi(2))
+            .sum::<f64>()
+            / runs as f64;
+
+        Stats {
+            runs,
+            mean,
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+            min: *samples.iter().min().unwrap(),
+            max: *samples.iter().max().unwrap(),
+        }
+    }
+}
+
+fn as_nanos<S: Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_u128(duration.as_nanos())
+}
+
+/// Timing of one step of a day: its parser (`part` is `None`) or an implementation of a part.
+#[derive(Debug, Clone, Serialize)]
+pub struct Measurement {
+    pub day: u8,
+    pub name: &'static str,
+    pub part: Option<u8>,
+    #[serde(flatten)]
+    pub stats: Stats,
+}
+
+fn measure<T>(options: Options, mut step: impl FnMut() -> T) -> Stats {
+    let mut samples = vec![];
+    let start = Instant::now();
+
+    // One sample at least, there are no stats of nothing.
+    while samples.len() < options.min_runs.max(1)
+        || (samples.len() < options.max_runs && start.elapsed() < options.budget)
+    {
+        let run = Instant::now();
+        black_box(step());
+        samples.push(run.elapsed());
+    }
+    Stats::from_samples(&samples)
+}
+
+/// Times the parser of `day` and every implementation of its parts, alternates included.
+pub fn bench_day(day: &Day, input: &str, options: Options) -> Result<Vec<Measurement>> {
+    let parsed = (day.parse)(input)?;
+    let mut measurements = vec![Measurement {
+        day: day.day,
+        name: "parse",
+        part: None,
+        stats: measure(options, || (day.parse)(input)),
+    }];
+
+    for implementation in day.implementations {
+        (implementation.run)(parsed.as_ref())?;
+        measurements.push(Measurement {
+            day: day.day,
+            name: implementation.name,
+            part: Some(match implementation.part {
+                Part::One => 1,
+                Part::Two => 2,
+            }),
+            stats: measure(options, || (implementation.run)(parsed.as_ref())),
+        });
+    }
+    Ok(measurements)
+}
+
+#[test]
+fn test_stats() {
+    let samples = [2, 4, 4, 4, 5, 5, 7, 9].map(Duration::from_millis);
+    let stats = Stats::from_samples(&samples);
+    assert_eq!(stats.runs, 8);
+    assert_eq!(stats.mean, Duration::from_millis(5));
+    assert_eq!(stats.stddev, Duration::from_millis(2));
+    assert_eq!(stats.min, Duration::from_millis(2));
+    assert_eq!(stats.max, Duration::from_millis(9));
+
+    assert_eq!(Options::runs(0).min_runs, 1);
+    let options = Options {
+        min_runs: 0,
+        max_runs: 0,
+        budget: Duration::default(),
+    };
+    assert_eq!(measure(options, || ()).runs, 1);
+}
@@ -1,40 +1,73 @@
+use crate::parse::{number, parse_lines};
+use crate::{Error, Part, Result, Solution};
 use std::collections::HashSet;
 
-pub fn p1(input: &str) -> i32 {
-    let set = build_set(input);
+pub struct Day01;
 
-    for x in &set {
-        match set.get(&(2020 - x)) {
-            Some(&y) => return x * y,
-            _ => continue,
-        }
-    }
-    unreachable!()
-}
+impl Solution for Day01 {
+    const DAY: u8 = 1;
+    type Input = HashSet<i32>;
+    type Answer = i32;
 
-pub fn p2(input: &str) -> i32 {
-    let set = build_set(input);
+    fn parse(input: &str) -> Result<HashSet<i32>> {
+        Ok(parse_lines(Self::DAY, input, |n| number(n, n))?
+            .into_iter()
+            .collect())
+    }
 
-    for x in &set {
-        for y in &set {
-            match set.get(&(2020 - x - y)) {
-                Some(&z) => return x * y * z,
+    fn p1(set: &HashSet<i32>) -> Result<i32> {
+        for x in set {
+            match set.get(&(2020 - x)) {
+                Some(&y) => return Ok(x * y),
                 _ => continue,
             }
         }
+        Err(Error::no_answer(
+            Self::DAY,
+            Part::One,
+            "no two entries sum to 2020",
+        ))
     }
-    unreachable!()
-}
 
-fn build_set(input: &str) -> HashSet<i32> {
-    input.lines().map(|n| n.parse::<i32>().unwrap()).collect()
+    fn p2(set: &HashSet<i32>) -> Result<i32> {
+        for x in set {
+            for y in set {
+                match set.get(&(2020 - x - y)) {
+                    Some(&z) => return Ok(x * y * z),
+                    _ => continue,
+                }
+            }
+        }
+        Err(Error::no_answer(
+            Self::DAY,
+            Part::Two,
+            "no three entries sum to 2020",
+        ))
+    }
 }
 
 #[test]
 fn test_p1() {
-    assert_eq!(p1(include_str!("../inputs/day01.txt")), 996075);
+    assert_eq!(Day01::solve_p1(&crate::input::load(1).unwrap()), Ok(996075));
 }
 #[test]
 fn test_p2() {
-    assert_eq!(p2(include_str!("../inputs/day01.txt")), 51810360);
+    assert_eq!(
+        Day01::solve_p2(&crate::input::load(1).unwrap()),
+        Ok(51810360)
+    );
+}
+
+#[test]
+fn test_malformed_line() {
+    assert_eq!(
+        Day01::parse("1721\n97x\n299"),
+        Err(Error::Parse {
+            day: 1,
+            line: 2,
+            column: 1,
+            text: String::from("97x"),
+            reason: String::from("`97x` is not a valid number"),
+        })
+    );
 }
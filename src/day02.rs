@@ -1,24 +1,53 @@
-use serde_scan::scan;
+use crate::error::Malformed;
+use crate::parse::{number, parse_lines};
+use crate::{Result, Solution};
 
-struct ParsedLine {
+pub struct ParsedLine {
     pos1: usize,
     pos2: usize,
     c: char,
     pwd: String,
 }
 
-pub fn p1(input: &str) -> usize {
-    input.lines().map(parse_line).filter(is_valid_pwd_1).count()
-}
+pub struct Day02;
+
+impl Solution for Day02 {
+    const DAY: u8 = 2;
+    type Input = Vec<ParsedLine>;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Vec<ParsedLine>> {
+        parse_lines(Self::DAY, input, parse_line)
+    }
 
-pub fn p2(input: &str) -> usize {
-    input.lines().map(parse_line).filter(is_valid_pwd_2).count()
+    fn p1(lines: &Vec<ParsedLine>) -> Result<usize> {
+        Ok(lines.iter().filter(|l| is_valid_pwd_1(l)).count())
+    }
+
+    fn p2(lines: &Vec<ParsedLine>) -> Result<usize> {
+        Ok(lines.iter().filter(|l| is_valid_pwd_2(l)).count())
+    }
 }
 
-fn parse_line(line: &str) -> ParsedLine {
-    let (pos1, pos2, c, pwd) = scan!("{}-{} {}: {}" <- line).unwrap();
+fn parse_line(line: &str) -> std::result::Result<ParsedLine, Malformed> {
+    // line format {pos1}-{pos2} {c}: {pwd}
+    let expected = || Malformed::new(1, "expected `{pos1}-{pos2} {char}: {password}`");
+    let (policy, pwd) = line.split_once(": ").ok_or_else(expected)?;
+    let (positions, c) = policy.split_once(" ").ok_or_else(expected)?;
+    let (pos1, pos2) = positions.split_once("-").ok_or_else(expected)?;
+
+    let mut chars = c.chars();
+    let c = match (chars.next(), chars.next()) {
+        (Some(c), None) => c,
+        _ => return Err(Malformed::at(line, c, "expected a single character")),
+    };
 
-    ParsedLine { pos1, pos2, c, pwd }
+    Ok(ParsedLine {
+        pos1: number(line, pos1)?,
+        pos2: number(line, pos2)?,
+        c,
+        pwd: pwd.to_string(),
+    })
 }
 
 fn is_valid_pwd_1(pwd_val: &ParsedLine) -> bool {
@@ -36,9 +65,9 @@ fn is_valid_pwd_2(pwd_val: &ParsedLine) -> bool {
 
 #[test]
 fn test_p1() {
-    assert_eq!(p1(include_str!("../inputs/day02.txt")), 524);
+    assert_eq!(Day02::solve_p1(&crate::input::load(2).unwrap()), Ok(524));
 }
 #[test]
 fn test_p2() {
-    assert_eq!(p2(include_str!("../inputs/day02.txt")), 485);
+    assert_eq!(Day02::solve_p2(&crate::input::load(2).unwrap()), Ok(485));
 }
@@ -1,56 +1,57 @@
-use std::vec::Vec;
-
-type Forest = Vec<Vec<char>>;
-
-pub fn p1(input: &str) -> usize {
-    count_trees(&parse_forest(input), (3, 1))
-}
-
-pub fn p2(input: &str) -> usize {
-    let forest = &parse_forest(input);
-    [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)]
-        .iter()
-        .map(|&slope| count_trees(forest, slope))
-        .product()
-}
-
-fn parse_forest(input: &str) -> Forest {
-    input.lines().map(|l| l.chars().collect()).collect()
+use crate::grid::{Addressing, Grid, Point};
+use crate::{Result, Solution};
+
+type Forest = Grid<char>;
+
+pub struct Day03;
+
+impl Solution for Day03 {
+    const DAY: u8 = 3;
+    type Input = Forest;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Forest> {
+        let forest = Grid::parse(Self::DAY, input, |c| match c {
+            '#' | '.' => Ok(c),
+            _ => Err(format!("unknown square `{}`", c)),
+        })?;
+        Ok(forest.with_addressing(Addressing::Wrapping, Addressing::Bounded))
+    }
+
+    fn p1(forest: &Forest) -> Result<usize> {
+        Ok(count_trees(forest, (3, 1)))
+    }
+
+    fn p2(forest: &Forest) -> Result<usize> {
+        Ok([(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)]
+            .iter()
+            .map(|&slope| count_trees(forest, slope))
+            .product())
+    }
 }
 
-fn walk_into_forest<'a>(
-    forest: &'a Forest,
-    slope: (usize, usize),
-) -> impl Iterator<Item = char> + 'a {
-    let mut x = 0;
-    let mut y = 0;
-    std::iter::from_fn(move || {
-        if y >= forest.len() {
-            return None;
-        }
-
-        let c = Some(forest[y][x]);
-        x = (x + slope.0) % forest[0].len();
-        y += slope.1;
-        c
-    })
+fn walk_into_forest(forest: &Forest, slope: Point) -> impl Iterator<Item = char> + '_ {
+    forest
+        .get((0, 0))
+        .into_iter()
+        .chain(forest.ray((0, 0), slope).map(|(_, square)| square))
+        .copied()
 }
 
-fn count_trees(forest: &Forest, slope: (usize, usize)) -> usize {
+fn count_trees(forest: &Forest, slope: Point) -> usize {
     walk_into_forest(forest, slope)
-        .filter(|c| match c {
-            '#' => return true,
-            '.' => return false,
-            _ => unreachable!(),
-        })
+        .filter(|&c| c == '#')
         .count()
 }
 
 #[test]
 fn test_p1() {
-    assert_eq!(p1(include_str!("../inputs/day03.txt")), 187);
+    assert_eq!(Day03::solve_p1(&crate::input::load(3).unwrap()), Ok(187));
 }
 #[test]
 fn test_p2() {
-    assert_eq!(p2(include_str!("../inputs/day03.txt")), 4723283400);
+    assert_eq!(
+        Day03::solve_p2(&crate::input::load(3).unwrap()),
+        Ok(4723283400)
+    );
 }
@@ -1,118 +1,235 @@
-use serde_scan::scan;
+use crate::parse::blocks;
+use crate::{Result, Solution};
+use schema::{Reason, Schema};
 use std::vec::Vec;
 
+pub mod record;
+pub mod report;
+pub mod schema;
+
 struct PassportField {
     key: String,
     val: String,
+    /// Whether the field was written `{key}:{value}`, the whole token is the
+    /// key otherwise.
+    separated: bool,
 }
 
 impl PassportField {
     fn from_str(raw_field: &str) -> Self {
-        let (key, val) = scan!("{}:{}" <- raw_field).unwrap();
-        PassportField { key, val }
+        let (key, val, separated) = match raw_field.split_once(':') {
+            Some((key, val)) => (key, val, true),
+            None => (raw_field, "", false),
+        };
+        PassportField {
+            key: key.to_string(),
+            val: val.to_string(),
+            separated,
+        }
     }
 
-    fn ecl_vals() -> [&'static str; 7] {
-        ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"]
+    /// Whether the field is one of `schema`, whatever its value.
+    fn is_known(&self, schema: &Schema) -> bool {
+        self.separated && schema.field(&self.key).is_some()
     }
 
-    fn is_valid(&self) -> bool {
-        match self.key.as_str() {
-            "byr" => (1920..=2002).contains(&self.val.parse::<u32>().unwrap()),
-            "iyr" => (2010..=2020).contains(&self.val.parse::<u32>().unwrap()),
-            "eyr" => (2020..=2030).contains(&self.val.parse::<u32>().unwrap()),
-            "hgt" => {
-                let last2 = &self.val[(self.val.len() - 2)..];
-                match last2 {
-                    "cm" => (150..=193)
-                        .contains(&self.val[..self.val.len() - 2].parse::<usize>().unwrap()),
-                    "in" => (59..=76)
-                        .contains(&self.val[..self.val.len() - 2].parse::<usize>().unwrap()),
-                    _ => false,
-                }
-            }
-            "hcl" => {
-                self.val.len() == 7
-                    && self.val.starts_with("#")
-                    && self.val[1..].chars().all(|c| c.is_ascii_hexdigit())
-            }
-            "ecl" => Self::ecl_vals().contains(&self.val.as_str()),
-            "pid" => self.val.len() == 9 && self.val.chars().all(|c| c.is_numeric()),
-            "cid" => true,
-            _ => unreachable!(),
+    fn check(&self, schema: &Schema) -> std::result::Result<(), Reason> {
+        if !self.separated {
+            return Err(Reason::MissingSeparator);
         }
+        schema
+            .field(&self.key)
+            .ok_or(Reason::UnknownKey)?
+            .check(&self.val)
     }
 }
 
-struct Passport {
+pub struct Passport {
     fields: Vec<PassportField>,
+    /// First and last lines of the passport in the input.
+    lines: (usize, usize),
 }
 
 impl Passport {
-    fn required_field_keys() -> [&'static str; 7] {
-        ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"]
+    fn has_all_required_fields(&self, schema: &Schema) -> bool {
+        schema.required().all(|key| {
+            self.fields
+                .iter()
+                .any(|f| f.key == key && f.is_known(schema))
+        })
     }
 
-    fn has_all_required_fields(&self) -> bool {
+    /// Fields with why they are rejected, in order. Only the first value of a
+    /// known key counts, the next ones are duplicates.
+    fn problems<'p>(
+        &'p self,
+        schema: &'p Schema,
+    ) -> impl Iterator<Item = (&'p PassportField, Reason)> {
         self.fields
             .iter()
-            .filter(|f| Self::required_field_keys().contains(&f.key.as_str()))
-            .count()
-            == Self::required_field_keys().len()
+            .enumerate()
+            .filter_map(move |(i, field)| {
+                let known = |f: &PassportField| f.key == field.key && f.is_known(schema);
+                if known(field) && self.fields[..i].iter().any(known) {
+                    return Some((field, Reason::Duplicate));
+                }
+                field.check(schema).err().map(|reason| (field, reason))
+            })
     }
 
-    fn is_valid(&self) -> bool {
-        self.has_all_required_fields() && self.fields.iter().all(|f| f.is_valid())
+    fn is_valid(&self, schema: &Schema) -> bool {
+        self.has_all_required_fields(schema) && self.problems(schema).next().is_none()
     }
 }
 
-pub fn p1(input: &str) -> usize {
-    input
-        .split("\n\n")
-        .filter_map(parse_passport_and_check_required_fields)
-        .count()
+/// Documents and the schema they follow.
+pub struct Batch {
+    schema: Schema,
+    passports: Vec<Passport>,
 }
 
-pub fn p2(input: &str) -> usize {
-    input
-        .split("\n\n")
-        .filter_map(parse_passport_and_check_valid_fields)
-        .count()
-}
+impl Batch {
+    /// Splits blank-line separated documents into fields. Fields that are
+    /// malformed or unknown to `schema` make their document invalid.
+    pub fn parse(input: &str, schema: Schema) -> Self {
+        let passports = blocks(input)
+            .iter()
+            .map(|lines| parse_passport(lines))
+            .collect();
+        Batch { schema, passports }
+    }
 
-fn parse_fields(raw_fields: &str) -> Vec<PassportField> {
-    raw_fields.split(" ").map(PassportField::from_str).collect()
-}
+    pub fn len(&self) -> usize {
+        self.passports.len()
+    }
 
-fn parse_passport_and_check_required_fields(line: &str) -> Option<Passport> {
-    let p = Passport {
-        fields: parse_fields(&line.replace("\n", " ")),
-    };
+    pub fn is_empty(&self) -> bool {
+        self.passports.is_empty()
+    }
 
-    if p.has_all_required_fields() {
-        return Some(p);
+    /// How many documents have every required field.
+    pub fn complete(&self) -> usize {
+        self.passports
+            .iter()
+            .filter(|p| p.has_all_required_fields(&self.schema))
+            .count()
     }
 
-    None
+    /// How many documents have every required field, all of them valid.
+    pub fn valid(&self) -> usize {
+        self.passports
+            .iter()
+            .filter(|p| p.is_valid(&self.schema))
+            .count()
+    }
 }
 
-fn parse_passport_and_check_valid_fields(line: &str) -> Option<Passport> {
-    let p = Passport {
-        fields: parse_fields(&line.replace("\n", " ")),
-    };
+pub struct Day04;
+
+impl Solution for Day04 {
+    const DAY: u8 = 4;
+    type Input = Batch;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Batch> {
+        Ok(Batch::parse(input, Schema::passport()))
+    }
+
+    fn p1(batch: &Batch) -> Result<usize> {
+        Ok(batch.complete())
+    }
+
+    fn p2(batch: &Batch) -> Result<usize> {
+        Ok(batch.valid())
+    }
+}
 
-    if p.is_valid() {
-        return Some(p);
+fn parse_passport(lines: &[(usize, &str)]) -> Passport {
+    let fields = lines
+        .iter()
+        .flat_map(|(_, line)| line.split_whitespace())
+        .map(PassportField::from_str)
+        .collect();
+    let first = lines.first().map_or(0, |&(i, _)| i);
+    let last = lines.last().map_or(0, |&(i, _)| i);
+    Passport {
+        fields,
+        lines: (first, last),
     }
+}
 
-    None
+#[test]
+fn test_custom_schema() {
+    let schema = Schema::from_toml(
+        "[fields.name]
+[fields.age]
+required = false
+constraint = { type = \"int\", min = 0, max = 150 }",
+    )
+    .unwrap();
+    let batch = Batch::parse("name:Ada age:36\n\nage:200\nname:Bob\n\nage:1", schema);
+    assert_eq!((batch.len(), batch.complete(), batch.valid()), (3, 2, 1));
+
+    let schema = Schema::from_toml("[fields.name]").unwrap();
+    let batch = Batch::parse("name:Ada age:36", schema);
+    assert_eq!((batch.len(), batch.complete(), batch.valid()), (1, 1, 0));
+    assert_eq!(batch.reports()[0].invalid[0].reason, Reason::UnknownKey);
+}
+
+#[test]
+fn test_malformed_values() {
+    let input = "  byr:19x0\tiyr:2015  eyr:2025 hgt:cm
+hcl:#123abc ecl:brn pid:000000001 byr:1990   \r
+\r
+ecl:brn byr: iyr:2015 eyr:2025 hgt:190cm hcl:#123abc pid:000000001 eyr:2025
+
+
+   \t
+";
+    let batch = Batch::parse(input, Schema::passport());
+    assert_eq!((batch.len(), batch.complete(), batch.valid()), (2, 2, 0));
+
+    let reasons: Vec<Vec<(String, Reason)>> = batch
+        .reports()
+        .into_iter()
+        .map(|r| r.invalid.into_iter().map(|f| (f.key, f.reason)).collect())
+        .collect();
+    assert_eq!(
+        reasons[0],
+        vec![
+            (String::from("byr"), Reason::NotAnInteger),
+            (String::from("hgt"), Reason::NotAnInteger),
+            (String::from("byr"), Reason::Duplicate),
+        ]
+    );
+    assert_eq!(
+        reasons[1],
+        vec![
+            (String::from("byr"), Reason::NotAnInteger),
+            (String::from("eyr"), Reason::Duplicate),
+        ]
+    );
+
+    // A token without `:` or with an unknown key only rejects its document.
+    let batch = Batch::parse(
+        "byr:1990 iyr hgt:180cm\n\nhcl:#123abc wat:1\n\necl:brn",
+        Schema::passport(),
+    );
+    assert_eq!((batch.len(), batch.complete(), batch.valid()), (3, 0, 0));
+    let reports = batch.reports();
+    assert_eq!(reports[0].missing, vec!["ecl", "eyr", "hcl", "iyr", "pid"]);
+    assert_eq!(reports[0].invalid[0].key, "iyr");
+    assert_eq!(reports[0].invalid[0].reason, Reason::MissingSeparator);
+    assert_eq!(reports[1].invalid[0].key, "wat");
+    assert_eq!(reports[1].invalid[0].reason, Reason::UnknownKey);
+    assert!(reports[2].invalid.is_empty());
 }
 
 #[test]
 fn test_p1() {
-    assert_eq!(p1(include_str!("../inputs/day04.txt")), 264);
+    assert_eq!(Day04::solve_p1(&crate::input::load(4).unwrap()), Ok(264));
 }
 #[test]
 fn test_p2() {
-    assert_eq!(p2(include_str!("../inputs/day04.txt")), 224);
+    assert_eq!(Day04::solve_p2(&crate::input::load(4).unwrap()), Ok(224));
 }
@@ -0,0 +1,360 @@
+//! Valid passports turned into typed records, exported as CSV or JSON.
+
+use super::{Batch, Passport};
+use serde::Serialize;
+use std::convert::TryFrom;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+const CM_PER_INCH: f64 = 2.54;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Unit {
+    Cm,
+    In,
+}
+
+impl FromStr for Unit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "cm" => Ok(Unit::Cm),
+            "in" => Ok(Unit::In),
+            _ => Err(format!("unknown unit {}, expected cm or in", s)),
+        }
+    }
+}
+
+impl Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Unit::Cm => write!(f, "cm"),
+            Unit::In => write!(f, "in"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Height {
+    pub value: f64,
+    pub unit: Unit,
+}
+
+impl Height {
+    /// The same height in `unit`, not rounded.
+    pub fn to(self, unit: Unit) -> Height {
+        let value = match (self.unit, unit) {
+            (Unit::Cm, Unit::In) => self.value / CM_PER_INCH,
+            (Unit::In, Unit::Cm) => self.value * CM_PER_INCH,
+            _ => self.value,
+        };
+        Height { value, unit }
+    }
+}
+
+impl Display for Height {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.value, self.unit)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Rgb {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EyeColour {
+    Amber,
+    Blue,
+    Brown,
+    Grey,
+    Green,
+    Hazel,
+    Other,
+}
+
+impl EyeColour {
+    /// The three letter code used by passports.
+    pub fn code(self) -> &'static str {
+        match self {
+            EyeColour::Amber => "amb",
+            EyeColour::Blue => "blu",
+            EyeColour::Brown => "brn",
+            EyeColour::Grey => "gry",
+            EyeColour::Green => "grn",
+            EyeColour::Hazel => "hzl",
+            EyeColour::Other => "oth",
+        }
+    }
+
+    fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "amb" => Some(EyeColour::Amber),
+            "blu" => Some(EyeColour::Blue),
+            "brn" => Some(EyeColour::Brown),
+            "gry" => Some(EyeColour::Grey),
+            "grn" => Some(EyeColour::Green),
+            "hzl" => Some(EyeColour::Hazel),
+            "oth" => Some(EyeColour::Other),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValidatedPassport {
+    pub birth_year: u16,
+    pub issue_year: u16,
+    pub expiration_year: u16,
+    pub height: Height,
+    pub hair_colour: Rgb,
+    pub eye_colour: EyeColour,
+    pub passport_id: String,
+    pub country_id: Option<String>,
+}
+
+/// Why a passport can't be turned into a record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordError {
+    Missing(&'static str),
+    Invalid { key: &'static str, value: String },
+}
+
+impl Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecordError::Missing(key) => write!(f, "missing {}", key),
+            RecordError::Invalid { key, value } => write!(f, "invalid {} `{}`", key, value),
+        }
+    }
+}
+
+fn parse_height(value: &str) -> Option<Height> {
+    [Unit::Cm, Unit::In].iter().find_map(|&unit| {
+        let number: u32 = value.strip_suffix(&unit.to_string())?.parse().ok()?;
+        Some(Height {
+            value: number.into(),
+            unit,
+        })
+    })
+}
+
+fn parse_rgb(value: &str) -> Option<Rgb> {
+    let hex = value.strip_prefix('#').filter(|hex| hex.len() == 6)?;
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some(Rgb {
+        red: channel(0)?,
+        green: channel(2)?,
+        blue: channel(4)?,
+    })
+}
+
+impl TryFrom<&Passport> for ValidatedPassport {
+    type Error = RecordError;
+
+    /// Reads the typed fields, checking their format but not the ranges of
+    /// the schema: use [`Batch::records`] for passports that are also valid.
+    fn try_from(passport: &Passport) -> Result<Self, RecordError> {
+        let value = |key| {
+            passport
+                .fields
+                .iter()
+                .find(|f| f.key == key && f.separated)
+                .map(|f| f.val.as_str())
+        };
+        let field = |key| value(key).ok_or(RecordError::Missing(key));
+        let invalid = |key, value: &str| RecordError::Invalid {
+            key,
+            value: value.to_string(),
+        };
+        let year = |key| {
+            let value = field(key)?;
+            value.parse().map_err(|_| invalid(key, value))
+        };
+
+        let height = field("hgt")?;
+        let hair = field("hcl")?;
+        let eyes = field("ecl")?;
+        let id = field("pid")?;
+        if id.is_empty() || !id.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid("pid", id));
+        }
+        Ok(ValidatedPassport {
+            birth_year: year("byr")?,
+            issue_year: year("iyr")?,
+            expiration_year: year("eyr")?,
+            height: parse_height(height).ok_or_else(|| invalid("hgt", height))?,
+            hair_colour: parse_rgb(hair).ok_or_else(|| invalid("hcl", hair))?,
+            eye_colour: EyeColour::from_code(eyes).ok_or_else(|| invalid("ecl", eyes))?,
+            passport_id: id.to_string(),
+            country_id: value("cid").map(String::from),
+        })
+    }
+}
+
+impl Batch {
+    /// Records of the valid documents, in order, and why the valid documents
+    /// that aren't passports can't be records, with their index from 1 as in
+    /// [`Report`](super::report::Report).
+    pub fn records(&self) -> (Vec<ValidatedPassport>, Vec<(usize, RecordError)>) {
+        let mut records = vec![];
+        let mut failures = vec![];
+        for (i, passport) in self.passports.iter().enumerate() {
+            if !passport.is_valid(&self.schema) {
+                continue;
+            }
+            match ValidatedPassport::try_from(passport) {
+                Ok(record) => records.push(record),
+                Err(error) => failures.push((i + 1, error)),
+            }
+        }
+        (records, failures)
+    }
+}
+
+/// Quotes `value` if it would break the row.
+fn csv_field(value: &str) -> String {
+    match value.contains(&[',', '"', '\n', '\r'][..]) {
+        true => format!("\"{}\"", value.replace('"', "\"\"")),
+        false => value.to_string(),
+    }
+}
+
+pub fn to_csv(records: &[ValidatedPassport]) -> String {
+    let mut csv = String::from(
+        "birth_year,issue_year,expiration_year,height,height_unit,\
+         hair_colour,eye_colour,passport_id,country_id\n",
+    );
+    for record in records {
+        csv += &format!(
+            "{},{},{},{},{},{},{},{},{}\n",
+            record.birth_year,
+            record.issue_year,
+            record.expiration_year,
+            record.height.value,
+            record.height.unit,
+            record.hair_colour,
+            record.eye_colour.code(),
+            csv_field(&record.passport_id),
+            csv_field(record.country_id.as_deref().unwrap_or("")),
+        );
+    }
+    csv
+}
+
+pub fn to_json(records: &[ValidatedPassport]) -> String {
+    serde_json::to_string_pretty(records).expect("records serialize to JSON")
+}
+
+#[test]
+fn test_records() {
+    use super::schema::Schema;
+
+    let batch = Batch::parse(
+        "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+hcl:#623a2f
+
+eyr:2029 ecl:blu cid:\"1,2\" byr:1989
+iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm
+
+hgt:59cm ecl:zzz
+eyr:2038 hcl:74454a iyr:2023
+pid:3556412378 byr:2007",
+        Schema::passport(),
+    );
+    let (records, failures) = batch.records();
+    assert_eq!(records.len(), 2);
+    assert!(failures.is_empty());
+    assert_eq!(
+        records[0],
+        ValidatedPassport {
+            birth_year: 1980,
+            issue_year: 2012,
+            expiration_year: 2030,
+            height: Height {
+                value: 74.0,
+                unit: Unit::In
+            },
+            hair_colour: Rgb {
+                red: 0x62,
+                green: 0x3a,
+                blue: 0x2f
+            },
+            eye_colour: EyeColour::Green,
+            passport_id: String::from("087499704"),
+            country_id: None,
+        }
+    );
+    assert_eq!(
+        ValidatedPassport::try_from(&batch.passports[2]),
+        Err(RecordError::Invalid {
+            key: "hcl",
+            value: String::from("74454a")
+        })
+    );
+
+    assert_eq!(
+        to_csv(&records),
+        "birth_year,issue_year,expiration_year,height,height_unit,\
+         hair_colour,eye_colour,passport_id,country_id
+1980,2012,2030,74,in,#623a2f,grn,087499704,
+1989,2014,2029,165,cm,#a97842,blu,896056539,\"\"\"1,2\"\"\"
+"
+    );
+    let json: serde_json::Value = serde_json::from_str(&to_json(&records)).unwrap();
+    assert_eq!(json[1]["height"]["unit"], "cm");
+    assert_eq!(json[1]["hair_colour"]["blue"], 0x42);
+    assert_eq!(json[1]["eye_colour"], "blue");
+    assert_eq!(json[0]["country_id"], serde_json::Value::Null);
+
+    let batch = Batch::parse(&crate::input::load(4).unwrap(), Schema::passport());
+    assert_eq!(batch.records().0.len(), 224);
+
+    // Valid under this schema, but none of them is a passport.
+    let schema = Schema::from_toml("[fields.byr]").unwrap();
+    let batch = Batch::parse("byr:1980\n\nhgt:180cm\n\nbyr:1990", schema);
+    let (records, failures) = batch.records();
+    assert!(records.is_empty());
+    assert_eq!(
+        failures,
+        vec![
+            (1, RecordError::Missing("hgt")),
+            (3, RecordError::Missing("hgt"))
+        ]
+    );
+}
+
+#[test]
+fn test_heights() {
+    let height = Height {
+        value: 74.0,
+        unit: Unit::In,
+    };
+    assert_eq!(height.to(Unit::Cm).value, 74.0 * 2.54);
+    assert_eq!(height.to(Unit::In), height);
+    assert!((height.to(Unit::Cm).to(Unit::In).value - 74.0).abs() < 1e-9);
+    assert_eq!(height.to(Unit::Cm).unit, Unit::Cm);
+    assert_eq!(
+        parse_height("190cm").map(|h| h.to_string()),
+        Some(String::from("190cm"))
+    );
+    assert_eq!(parse_height("cm"), None);
+    assert_eq!(parse_height("1é"), None);
+    assert_eq!(parse_height("1x0in"), None);
+    assert_eq!(
+        "ft".parse::<Unit>(),
+        Err(String::from("unknown unit ft, expected cm or in"))
+    );
+}
@@ -0,0 +1,153 @@
+//! Why documents are rejected: the fields they miss and the values breaking
+//! their constraint.
+
+use super::schema::{Reason, Schema};
+use super::{Batch, Passport};
+use serde::Serialize;
+use std::fmt::{self, Display};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InvalidField {
+    pub key: String,
+    pub value: String,
+    pub reason: Reason,
+    /// What the value should have been.
+    pub constraint: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Report {
+    /// Position of the document in the batch, from 1.
+    pub index: usize,
+    pub first_line: usize,
+    pub last_line: usize,
+    /// Required fields the document doesn't have, sorted.
+    pub missing: Vec<String>,
+    pub invalid: Vec<InvalidField>,
+}
+
+impl Report {
+    fn new(index: usize, passport: &Passport, schema: &Schema) -> Self {
+        let missing = schema
+            .required()
+            .filter(|&key| {
+                !passport
+                    .fields
+                    .iter()
+                    .any(|f| f.key == key && f.is_known(schema))
+            })
+            .map(String::from)
+            .collect();
+        let invalid = passport
+            .problems(schema)
+            .map(|(f, reason)| InvalidField {
+                key: f.key.clone(),
+                value: f.val.clone(),
+                reason,
+                constraint: schema
+                    .field(&f.key)
+                    .and_then(|field| field.constraint.as_ref())
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+            })
+            .collect();
+
+        Report {
+            index,
+            first_line: passport.lines.0,
+            last_line: passport.lines.1,
+            missing,
+            invalid,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.missing.is_empty() && self.invalid.is_empty()
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "document {} (lines {}-{}): ",
+            self.index, self.first_line, self.last_line
+        )?;
+        if self.is_valid() {
+            return write!(f, "valid");
+        }
+        write!(f, "rejected")?;
+        if !self.missing.is_empty() {
+            write!(f, "\n    missing {}", self.missing.join(", "))?;
+        }
+        for field in &self.invalid {
+            match field.reason {
+                // The whole token is the key, there is no value to show.
+                Reason::MissingSeparator => write!(f, "\n    `{}`: {}", field.key, field.reason)?,
+                Reason::Duplicate | Reason::UnknownKey => {
+                    write!(f, "\n    {} `{}`: {}", field.key, field.value, field.reason)?
+                }
+                _ => write!(
+                    f,
+                    "\n    {} `{}`: {}, expected {}",
+                    field.key, field.value, field.reason, field.constraint
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Batch {
+    /// A report for every document, in order.
+    pub fn reports(&self) -> Vec<Report> {
+        self.passports
+            .iter()
+            .enumerate()
+            .map(|(i, passport)| Report::new(i + 1, passport, &self.schema))
+            .collect()
+    }
+}
+
+pub fn to_json(reports: &[Report]) -> String {
+    serde_json::to_string_pretty(reports).expect("reports serialize to JSON")
+}
+
+#[test]
+fn test_reports() {
+    let batch = Batch::parse(
+        "eyr:1972 cid:100
+hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926
+
+pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+hcl:#623a2f
+
+hcl:#888785 hgt:164cm byr:2001 iyr:2015",
+        Schema::passport(),
+    );
+    let reports = batch.reports();
+    assert_eq!(reports.len(), 3);
+
+    assert_eq!((reports[0].first_line, reports[0].last_line), (1, 2));
+    assert!(reports[0].missing.is_empty());
+    let keys: Vec<&str> = reports[0].invalid.iter().map(|f| f.key.as_str()).collect();
+    assert_eq!(keys, vec!["eyr", "hgt", "pid"]);
+    assert!(reports[1].is_valid());
+    assert_eq!(reports[2].missing, vec!["ecl", "eyr", "pid"]);
+
+    assert_eq!(
+        reports[0].to_string(),
+        "document 1 (lines 1-2): rejected
+    eyr `1972`: 1972 is out of 2020..=2030, expected an integer in 2020..=2030
+    hgt `170`: missing or unknown unit, expected one of 150..=193 cm, 59..=76 in
+    pid `186cm`: no match, expected a value matching /[0-9]{9}/"
+    );
+    assert_eq!(reports[1].to_string(), "document 2 (lines 4-5): valid");
+
+    let json: serde_json::Value = serde_json::from_str(&to_json(&reports)).unwrap();
+    assert_eq!(json[2]["first_line"], 7);
+    assert_eq!(json[2]["missing"][1], "eyr");
+    assert_eq!(json[0]["invalid"][1]["value"], "170");
+    assert_eq!(json[0]["invalid"][1]["reason"]["kind"], "unknown_unit");
+    assert_eq!(json[0]["invalid"][0]["reason"]["min"], 2020);
+}
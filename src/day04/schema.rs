@@ -0,0 +1,379 @@
+//! Declarative schemas telling which fields a document has and what values
+//! they accept, loaded from TOML or JSON:
+//!
+//! ```toml
+//! [fields.hgt]
+//! constraint = { type = "unit", units = { cm = [150, 193], in = [59, 76] } }
+//!
+//! [fields.cid]
+//! required = false
+//! ```
+
+use crate::{Error, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt::{self, Display};
+use std::fs;
+use std::path::Path;
+
+/// The schema of part 2.
+static PASSPORT: &str = include_str!("passport.toml");
+
+/// A regex matching whole values only.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Pattern {
+    source: String,
+    regex: Regex,
+}
+
+impl TryFrom<String> for Pattern {
+    type Error = regex::Error;
+
+    fn try_from(source: String) -> std::result::Result<Self, regex::Error> {
+        // Compiled alone first so that errors show the pattern as written.
+        Regex::new(&source)?;
+        let regex = Regex::new(&format!("^(?:{})$", source))?;
+        Ok(Pattern { source, regex })
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "/{}/", self.source)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Constraint {
+    /// An integer in `min..=max`.
+    Int {
+        min: i64,
+        max: i64,
+    },
+    /// An integer followed by one of the units, in its range.
+    Unit {
+        units: BTreeMap<String, (i64, i64)>,
+    },
+    Regex {
+        pattern: Pattern,
+    },
+    /// One of the values.
+    Enum {
+        values: Vec<String>,
+    },
+}
+
+/// Why a value is rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Reason {
+    NotAnInteger,
+    OutOfRange {
+        value: i64,
+        min: i64,
+        max: i64,
+    },
+    /// The value ends with none of the units.
+    UnknownUnit,
+    NoMatch,
+    NotInSet,
+    /// The key was given before in the same document.
+    Duplicate,
+    /// The field isn't written `{key}:{value}`.
+    MissingSeparator,
+    /// The schema has no field with that key.
+    UnknownKey,
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Reason::NotAnInteger => write!(f, "not an integer"),
+            Reason::OutOfRange { value, min, max } => {
+                write!(f, "{} is out of {}..={}", value, min, max)
+            }
+            Reason::UnknownUnit => write!(f, "missing or unknown unit"),
+            Reason::NoMatch => write!(f, "no match"),
+            Reason::NotInSet => write!(f, "not an allowed value"),
+            Reason::Duplicate => write!(f, "key given more than once"),
+            Reason::MissingSeparator => write!(f, "expected `{{key}}:{{value}}`"),
+            Reason::UnknownKey => write!(f, "unknown field"),
+        }
+    }
+}
+
+fn in_range(value: &str, min: i64, max: i64) -> std::result::Result<(), Reason> {
+    let value = value.parse().map_err(|_| Reason::NotAnInteger)?;
+    match (min..=max).contains(&value) {
+        true => Ok(()),
+        false => Err(Reason::OutOfRange { value, min, max }),
+    }
+}
+
+impl Constraint {
+    pub fn check(&self, value: &str) -> std::result::Result<(), Reason> {
+        match self {
+            Constraint::Int { min, max } => in_range(value, *min, *max),
+            // The longest unit ending the value, so that `mm` wins over `m`.
+            Constraint::Unit { units } => units
+                .iter()
+                .filter(|(unit, _)| value.ends_with(unit.as_str()))
+                .max_by_key(|(unit, _)| unit.len())
+                .map_or(Err(Reason::UnknownUnit), |(unit, (min, max))| {
+                    in_range(&value[..value.len() - unit.len()], *min, *max)
+                }),
+            Constraint::Regex { pattern } if pattern.regex.is_match(value) => Ok(()),
+            Constraint::Regex { .. } => Err(Reason::NoMatch),
+            Constraint::Enum { values } if values.iter().any(|v| v == value) => Ok(()),
+            Constraint::Enum { .. } => Err(Reason::NotInSet),
+        }
+    }
+
+    pub fn accepts(&self, value: &str) -> bool {
+        self.check(value).is_ok()
+    }
+}
+
+impl Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Constraint::Int { min, max } => write!(f, "an integer in {}..={}", min, max),
+            Constraint::Unit { units } => {
+                let units: Vec<String> = units
+                    .iter()
+                    .map(|(unit, (min, max))| format!("{}..={} {}", min, max, unit))
+                    .collect();
+                write!(f, "one of {}", units.join(", "))
+            }
+            Constraint::Regex { pattern } => write!(f, "a value matching {}", pattern),
+            Constraint::Enum { values } => write!(f, "one of {}", values.join(", ")),
+        }
+    }
+}
+
+fn required_by_default() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Field {
+    #[serde(default = "required_by_default")]
+    pub required: bool,
+    /// What the value must be, anything if `None`.
+    pub constraint: Option<Constraint>,
+}
+
+impl Field {
+    pub fn check(&self, value: &str) -> std::result::Result<(), Reason> {
+        self.constraint.as_ref().map_or(Ok(()), |c| c.check(value))
+    }
+
+    pub fn accepts(&self, value: &str) -> bool {
+        self.check(value).is_ok()
+    }
+}
+
+/// `message` without the position both parsers end their messages with.
+fn without_position(message: String, line: usize, column: usize) -> String {
+    let position = format!(" at line {} column {}", line, column);
+    match message.strip_suffix(&position) {
+        Some(reason) => reason.to_string(),
+        None => message,
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Schema {
+    pub fields: BTreeMap<String, Field>,
+}
+
+impl Schema {
+    /// The passport fields and rules of the puzzle.
+    pub fn passport() -> Self {
+        Self::from_toml(PASSPORT).expect("the passport schema is valid")
+    }
+
+    pub fn from_toml(text: &str) -> std::result::Result<Self, String> {
+        toml::from_str(text).map_err(|e| e.to_string())
+    }
+
+    pub fn from_json(text: &str) -> std::result::Result<Self, String> {
+        serde_json::from_str(text).map_err(|e| e.to_string())
+    }
+
+    /// Reads a schema from a `.json` file, or TOML for any other extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+        Self::parse(path, &text)
+    }
+
+    /// The schema written in `text`, read from `path`.
+    fn parse(path: &Path, text: &str) -> Result<Self> {
+        let invalid = |line, column, message: String| Error::Schema {
+            path: path.display().to_string(),
+            line,
+            column,
+            reason: without_position(message, line, column),
+        };
+        match path.extension() == Some("json".as_ref()) {
+            true => {
+                serde_json::from_str(text).map_err(|e| invalid(e.line(), e.column(), e.to_string()))
+            }
+            false => toml::from_str(text).map_err(|e| {
+                let (line, column) = e.line_col().map_or((0, 0), |(l, c)| (l + 1, c + 1));
+                invalid(line, column, e.to_string())
+            }),
+        }
+    }
+
+    pub fn field(&self, key: &str) -> Option<&Field> {
+        self.fields.get(key)
+    }
+
+    /// Keys of the required fields, sorted.
+    pub fn required(&self) -> impl Iterator<Item = &str> {
+        self.fields
+            .iter()
+            .filter(|(_, field)| field.required)
+            .map(|(key, _)| key.as_str())
+    }
+}
+
+#[test]
+fn test_constraints() {
+    let schema = Schema::passport();
+    let accepts = |key: &str, value| schema.field(key).unwrap().accepts(value);
+    assert!(accepts("byr", "2002"));
+    assert!(!accepts("byr", "2003"));
+    assert!(!accepts("byr", "two"));
+    assert!(accepts("hgt", "60in"));
+    assert!(accepts("hgt", "190cm"));
+    assert!(!accepts("hgt", "190in"));
+    assert!(!accepts("hgt", "190"));
+    assert!(!accepts("hgt", "in"));
+    assert!(accepts("hcl", "#123abc"));
+    assert!(!accepts("hcl", "#123abz"));
+    assert!(!accepts("hcl", "123abc"));
+    assert!(accepts("ecl", "brn"));
+    assert!(!accepts("ecl", "wat"));
+    assert!(accepts("pid", "000000001"));
+    assert!(!accepts("pid", "0123456789"));
+    assert!(accepts("cid", "anything"));
+    assert_eq!(
+        schema.required().collect::<Vec<_>>(),
+        vec!["byr", "ecl", "eyr", "hcl", "hgt", "iyr", "pid"]
+    );
+}
+
+#[test]
+fn test_reasons() {
+    let schema = Schema::passport();
+    let check = |key: &str, value| schema.field(key).unwrap().check(value);
+    assert_eq!(check("byr", ""), Err(Reason::NotAnInteger));
+    assert_eq!(check("byr", "19x0"), Err(Reason::NotAnInteger));
+    assert_eq!(
+        check("byr", "99999999999999999999"),
+        Err(Reason::NotAnInteger)
+    );
+    assert_eq!(
+        check("byr", "1900"),
+        Err(Reason::OutOfRange {
+            value: 1900,
+            min: 1920,
+            max: 2002
+        })
+    );
+    assert_eq!(check("hgt", "cm"), Err(Reason::NotAnInteger));
+    assert_eq!(check("hgt", "m"), Err(Reason::UnknownUnit));
+    assert_eq!(check("hgt", ""), Err(Reason::UnknownUnit));
+    assert_eq!(check("hcl", "#12"), Err(Reason::NoMatch));
+    assert_eq!(check("ecl", "xyz"), Err(Reason::NotInSet));
+    assert_eq!(check("cid", ""), Ok(()));
+}
+
+#[test]
+fn test_overlapping_units() {
+    let schema = Schema::from_toml(
+        "[fields.len]
+constraint = { type = \"unit\", units = { m = [1, 10], mm = [1, 10000], cm = [1, 1000] } }",
+    )
+    .unwrap();
+    let check = |value| schema.field("len").unwrap().check(value);
+    assert_eq!(check("5mm"), Ok(()));
+    assert_eq!(check("5cm"), Ok(()));
+    assert_eq!(check("5m"), Ok(()));
+    assert_eq!(
+        check("5000cm"),
+        Err(Reason::OutOfRange {
+            value: 5000,
+            min: 1,
+            max: 1000
+        })
+    );
+    assert_eq!(check("5km"), Err(Reason::NotAnInteger));
+}
+
+#[test]
+fn test_formats() {
+    let schema = Schema::from_json(
+        r#"{"fields": {
+            "id": {"constraint": {"type": "regex", "pattern": "[A-Z]{2}[0-9]+"}},
+            "age": {"required": false, "constraint": {"type": "int", "min": 0, "max": 150}}
+        }}"#,
+    )
+    .unwrap();
+    assert_eq!(schema.required().collect::<Vec<_>>(), vec!["id"]);
+    assert!(schema.field("id").unwrap().accepts("AB12"));
+    assert!(!schema.field("id").unwrap().accepts("xAB12"));
+    assert_eq!(
+        schema
+            .field("age")
+            .unwrap()
+            .constraint
+            .as_ref()
+            .unwrap()
+            .to_string(),
+        "an integer in 0..=150"
+    );
+
+    assert!(
+        Schema::from_toml("[fields.id]\nconstraint = { type = \"regex\", pattern = \"(\" }")
+            .is_err()
+    );
+    assert!(Schema::from_toml("[fields.id]\nconstraint = { type = \"float\" }").is_err());
+}
+
+#[test]
+fn test_invalid_schemas() {
+    let error = Schema::parse(
+        Path::new("schema.toml"),
+        "[fields.id]\nconstraint = { type = \"float\" }",
+    )
+    .err()
+    .unwrap();
+    match error {
+        Error::Schema { line, .. } => assert_eq!(line, 2),
+        _ => panic!("expected a schema error, got {:?}", error),
+    }
+    assert_eq!(
+        error.to_string(),
+        "schema.toml, line 2, column 23: invalid schema, unknown variant `float`, \
+         expected one of `int`, `unit`, `regex`, `enum` for key `fields.id.constraint`"
+    );
+
+    assert_eq!(
+        Schema::parse(Path::new("schema.json"), "{\"fields\": {\n  \"id\": 1}}")
+            .err()
+            .map(|e| e.to_string()),
+        Some(String::from(
+            "schema.json, line 2, column 9: invalid schema, \
+             invalid type: integer `1`, expected struct Field"
+        ))
+    );
+    assert!(Schema::parse(Path::new("schema"), "[fields.id]").is_ok());
+}
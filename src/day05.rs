@@ -1,47 +1,71 @@
+use crate::error::Malformed;
+use crate::parse::parse_lines;
+use crate::{Error, Part, Result, Solution};
+
 static ROWS: usize = 128;
 static COLS: usize = 8;
 
-pub fn p1(input: &str) -> usize {
-    input
-        .lines()
-        .map(|l| row_col_to_ticket_id(ticket_to_row_col_pair(l)))
-        .max()
-        .unwrap()
-}
+pub struct Day05;
 
-pub fn p2(input: &str) -> usize {
-    let mut seats = input
-        .lines()
-        .map(|l| row_col_to_ticket_id(ticket_to_row_col_pair(l)))
-        .collect::<Vec<usize>>();
-    seats.sort();
-    seats
-        .iter()
-        .enumerate()
-        .find_map(|(i, &s)| {
-            if seats[i + 1] == s + 1 {
-                return None;
-            }
-            Some(s + 1)
+impl Solution for Day05 {
+    const DAY: u8 = 5;
+    type Input = Vec<usize>;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Vec<usize>> {
+        parse_lines(Self::DAY, input, |l| {
+            ticket_to_row_col_pair(l).map(row_col_to_ticket_id)
         })
-        .unwrap()
+    }
+
+    fn p1(seats: &Vec<usize>) -> Result<usize> {
+        seats
+            .iter()
+            .max()
+            .copied()
+            .ok_or_else(|| Error::no_answer(Self::DAY, Part::One, "there are no tickets"))
+    }
+
+    fn p2(seats: &Vec<usize>) -> Result<usize> {
+        let mut seats = seats.clone();
+        seats.sort();
+        seats
+            .windows(2)
+            .find_map(|w| {
+                if w[1] == w[0] + 1 {
+                    return None;
+                }
+                Some(w[0] + 1)
+            })
+            .ok_or_else(|| Error::no_answer(Self::DAY, Part::Two, "there is no free seat"))
+    }
 }
 
-fn ticket_to_row_col_pair(ticket: &str) -> (usize, usize) {
+fn ticket_to_row_col_pair(ticket: &str) -> std::result::Result<(usize, usize), Malformed> {
     let mut row = 0..ROWS;
     let mut col = 0..COLS;
 
-    ticket.chars().for_each(|c| {
-        match c {
-            'F' => row = row.start..(row.end - (row.end - row.start) / 2),
-            'B' => row = (row.end - (row.end - row.start) / 2)..row.end,
-            'L' => col = col.start..(col.end - (col.end - col.start) / 2),
-            'R' => col = (col.end - (col.end - col.start) / 2)..col.end,
-            _ => unreachable!(),
+    if ticket.len() != 10 {
+        return Err(Malformed::new(1, "expected 10 characters"));
+    }
+
+    for (i, c) in ticket.char_indices() {
+        match (i, c) {
+            (0..=6, 'F') => row = row.start..(row.end - (row.end - row.start) / 2),
+            (0..=6, 'B') => row = (row.end - (row.end - row.start) / 2)..row.end,
+            (7..=9, 'L') => col = col.start..(col.end - (col.end - col.start) / 2),
+            (7..=9, 'R') => col = (col.end - (col.end - col.start) / 2)..col.end,
+            _ => {
+                return Err(Malformed::at(
+                    ticket,
+                    &ticket[i..],
+                    format!("unexpected `{}`", c),
+                ))
+            }
         };
-    });
+    }
 
-    (row.start, col.start)
+    Ok((row.start, col.start))
 }
 
 fn row_col_to_ticket_id(row_col: (usize, usize)) -> usize {
@@ -50,10 +74,10 @@ fn row_col_to_ticket_id(row_col: (usize, usize)) -> usize {
 
 #[test]
 fn test_p1() {
-    assert_eq!(p1(include_str!("../inputs/day05.txt")), 933);
+    assert_eq!(Day05::solve_p1(&crate::input::load(5).unwrap()), Ok(933));
 }
 
 #[test]
 fn test_p2() {
-    assert_eq!(p2(include_str!("../inputs/day05.txt")), 711);
+    assert_eq!(Day05::solve_p2(&crate::input::load(5).unwrap()), Ok(711));
 }
@@ -1,45 +1,59 @@
+use crate::parse::blocks;
+use crate::{Result, Solution};
 use std::collections::HashSet;
 
-pub fn p1(input: &str) -> usize {
-    input
-        .split("\n\n")
-        .map(|group| {
-            group
-                .lines()
-                .fold(HashSet::new(), fold_lines_to_answers_set)
-                .len()
-        })
-        .sum()
-}
+type Answers = HashSet<char>;
+type Group = Vec<Answers>;
+
+pub struct Day06;
+
+impl Solution for Day06 {
+    const DAY: u8 = 6;
+    type Input = Vec<Group>;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Vec<Group>> {
+        Ok(blocks(input)
+            .into_iter()
+            .map(|group| group.iter().map(|(_, l)| line_to_answer_set(l)).collect())
+            .collect())
+    }
+
+    fn p1(groups: &Vec<Group>) -> Result<usize> {
+        Ok(groups
+            .iter()
+            .map(|group| group.iter().fold(HashSet::new(), fold_answers_sets).len())
+            .sum())
+    }
 
-pub fn p2(input: &str) -> usize {
-    input
-        .split("\n\n")
-        .map(|group| {
-            group
-                .lines()
-                .map(line_to_answer_set)
-                .fold_first(|acc, hs| acc.intersection(&hs).cloned().collect())
-                .unwrap()
-                .len()
-        })
-        .sum()
+    fn p2(groups: &Vec<Group>) -> Result<usize> {
+        Ok(groups
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .cloned()
+                    .reduce(|acc, hs| acc.intersection(&hs).cloned().collect())
+                    .map_or(0, |answers| answers.len())
+            })
+            .sum())
+    }
 }
 
-fn line_to_answer_set(line: &str) -> HashSet<char> {
+fn line_to_answer_set(line: &str) -> Answers {
     line.chars().collect()
 }
 
-fn fold_lines_to_answers_set(set: HashSet<char>, line: &str) -> HashSet<char> {
-    set.union(&line_to_answer_set(line)).cloned().collect()
+fn fold_answers_sets(set: Answers, answers: &Answers) -> Answers {
+    set.union(answers).cloned().collect()
 }
 
 #[test]
 fn test_p1() {
-    assert_eq!(p1(include_str!("../inputs/day06.txt")), 6416);
+    assert_eq!(Day06::solve_p1(&crate::input::load(6).unwrap()), Ok(6416));
 }
 
 #[test]
 fn test_p2() {
-    assert_eq!(p2(include_str!("../inputs/day06.txt")), 3050);
+    assert_eq!(Day06::solve_p2(&crate::input::load(6).unwrap()), Ok(3050));
 }
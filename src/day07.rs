@@ -1,128 +1,205 @@
-use std::borrow::Borrow;
-use std::collections::{HashMap, HashSet};
-use std::hash::{Hash, Hasher};
+use crate::error::Malformed;
+use crate::parse::{number, parse_lines};
+use crate::{Error, Part, Result, Solution};
+use graph::{BagGraph, RuleError};
 
-static MY_BAG: &'static str = "shiny gold";
-static NO_DEP: &'static str = "no other bags";
+pub mod graph;
+pub mod query;
+pub mod render;
 
-type BagDependencies<'a> = HashSet<Bag<'a>>;
+pub static MY_BAG: &str = "shiny gold";
+static NO_DEP: &str = "no other bags";
 
-type BagMapEntry<'a> = (&'a str, BagDependencies<'a>);
-type BagMap<'a> = HashMap<&'a str, BagDependencies<'a>>;
+/// The bags a rule lists, in order, a colour listed twice staying twice.
+type BagDependencies = Vec<Bag>;
+
+type BagMapEntry = (String, BagDependencies);
 
 #[derive(Debug)]
-struct Bag<'a> {
-    name: &'a str,
+pub struct Bag {
+    name: String,
     amount: usize,
 }
 
-impl<'a> Hash for Bag<'a> {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.name.hash(state);
-    }
-}
+pub struct Day07;
 
-impl<'a> PartialEq for Bag<'a> {
-    fn eq(&self, other: &Self) -> bool {
-        self.name == other.name
-    }
-}
+impl Solution for Day07 {
+    const DAY: u8 = 7;
+    type Input = BagGraph;
+    type Answer = usize;
 
-impl<'a> Eq for Bag<'a> {}
-
-impl<'a> Borrow<&'a str> for Bag<'a> {
-    fn borrow(&self) -> &&'a str {
-        &self.name
+    fn parse(input: &str) -> Result<BagGraph> {
+        build_bag_graph(input)
     }
-}
 
-pub fn p1(input: &str) -> usize {
-    let bag_map = build_bag_map(input);
-
-    println!("{:#?}", bag_map);
+    fn p1(graph: &BagGraph) -> Result<usize> {
+        Ok(graph.ancestors(MY_BAG).len())
+    }
 
-    bag_map
-        .keys()
-        .filter(|name| bag_can_contain(&bag_map, name, MY_BAG))
-        .count()
+    fn p2(graph: &BagGraph) -> Result<usize> {
+        if !graph.is_defined(MY_BAG) {
+            return Err(Error::no_answer(
+                Self::DAY,
+                Part::Two,
+                format!("there is no rule for {} bags", MY_BAG),
+            ));
+        }
+        Ok(graph.total_contents(MY_BAG).unwrap_or(0))
+    }
 }
 
-pub fn p2(input: &str) -> usize {
-    let bag_map = build_bag_map(input);
-    bag_count(&bag_map, MY_BAG, 1) - 1
+fn build_bag_graph(input: &str) -> Result<BagGraph> {
+    let graph = BagGraph::new(parse_lines(Day07::DAY, input, parse_line)?);
+    let errors = graph
+        .validate()
+        .iter()
+        .map(|error| {
+            let line = error.line();
+            let text = input.lines().nth(line - 1).unwrap_or("");
+            let token = match error {
+                RuleError::Duplicate { .. } => text,
+                RuleError::Undefined { colour, .. } | RuleError::Repeated { colour, .. } => {
+                    find_dependency(text, colour)
+                }
+                RuleError::Cycle { path, .. } => find_dependency(text, &path[1]),
+            };
+            Malformed::at(text, token, error.to_string()).into_error(Day07::DAY, line, text)
+        })
+        .collect();
+    match Error::all(errors) {
+        None => Ok(graph),
+        Some(error) => Err(error),
+    }
 }
 
-fn build_bag_map(input: &str) -> BagMap {
-    input.lines().map(parse_line).collect()
+/// Where `colour` is last among the bags `line` contains, the whole line if it isn't.
+fn find_dependency<'a>(line: &'a str, colour: &str) -> &'a str {
+    let bag = format!("{} bag", colour);
+    line.split_once(" contain ")
+        .and_then(|(_, dependencies)| {
+            dependencies
+                .split(", ")
+                .filter(|dep| {
+                    dep.split_once(' ')
+                        .is_some_and(|(_, rest)| rest.starts_with(&bag))
+                })
+                .last()
+        })
+        .unwrap_or(line)
 }
 
-fn parse_line(line: &str) -> BagMapEntry {
+fn parse_line(line: &str) -> std::result::Result<BagMapEntry, Malformed> {
     let kv = line
         .strip_suffix(".")
-        .unwrap()
+        .ok_or_else(|| Malformed::new(line.chars().count(), "expected a final `.`"))?
         .split_once(" contain ")
-        .unwrap();
+        .ok_or_else(|| Malformed::new(1, "expected `{bag_name} bags contain ...`"))?;
 
-    (parse_key(kv.0), parse_dependencies(kv.1))
+    Ok((
+        parse_key(line, kv.0)?.to_string(),
+        parse_dependencies(line, kv.1)?,
+    ))
 }
 
-fn parse_key(raw_key: &str) -> &str {
+fn parse_key<'a>(line: &str, raw_key: &'a str) -> std::result::Result<&'a str, Malformed> {
     // raw_dependencies format {bag_name} bags
-    raw_key.strip_suffix("bags").unwrap().trim()
+    Ok(raw_key
+        .strip_suffix("bags")
+        .ok_or_else(|| Malformed::at(line, raw_key, "expected `{bag_name} bags`"))?
+        .trim())
 }
 
-fn parse_dependencies(raw_dependencies: &str) -> BagDependencies {
+fn parse_dependencies(
+    line: &str,
+    raw_dependencies: &str,
+) -> std::result::Result<BagDependencies, Malformed> {
     // raw_dependencies format {bag_amount} {bag_name} bag(s), ...
     if raw_dependencies.contains(NO_DEP) {
-        return HashSet::new();
+        return Ok(vec![]);
     }
 
     raw_dependencies
         .split(", ")
         .map(|dep| {
-            let (number, name) = dep
+            let expected = || Malformed::at(line, dep, "expected `{bag_amount} {bag_name} bag(s)`");
+            let (amount, name) = dep
                 .strip_suffix("s")
                 .unwrap_or(dep)
                 .strip_suffix("bag")
-                .unwrap()
+                .ok_or_else(expected)?
                 .split_once(" ")
-                .unwrap();
+                .ok_or_else(expected)?;
 
-            Bag {
-                amount: number.parse::<usize>().unwrap(),
-                name: name.trim(),
-            }
+            Ok(Bag {
+                amount: number(line, amount)?,
+                name: name.trim().to_string(),
+            })
         })
         .collect()
 }
 
-fn bag_can_contain(bag_map: &BagMap, key: &str, bag_to_find: &str) -> bool {
-    let dependencies = bag_map.get(key).unwrap();
-    dependencies.contains(&bag_to_find)
-        || dependencies
-            .iter()
-            .find(|dep| bag_can_contain(bag_map, dep.name, bag_to_find))
-            .is_some()
+/// The example rules of the puzzle.
+#[cfg(test)]
+fn example() -> BagGraph {
+    Day07::parse(
+        "light red bags contain 1 bright white bag, 2 muted yellow bags.
+dark orange bags contain 3 bright white bags, 4 muted yellow bags.
+bright white bags contain 1 shiny gold bag.
+muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
+shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
+dark olive bags contain 3 faded blue bags, 4 dotted black bags.
+vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
+faded blue bags contain no other bags.
+dotted black bags contain no other bags.",
+    )
+    .unwrap()
 }
 
-fn bag_count(bag_map: &BagMap, bag: &str, count: usize) -> usize {
-    let dependencies = bag_map.get(bag).unwrap();
-    if dependencies.is_empty() {
-        return count;
-    }
-    dependencies
-        .iter()
-        .map(|b| bag_count(bag_map, b.name, b.amount * count))
-        .sum::<usize>()
-        + count
+#[test]
+fn test_invalid_rules() {
+    let error = |input| Day07::parse(input).err().unwrap().to_string();
+    assert!(
+        error("red bags contain 1 blue bag.\nred bags contain no other bags.")
+            .contains("line 2, column 1: red bags have a rule on lines 1, 2")
+    );
+    assert!(
+        error("red bags contain 2 blue bags, 1 teal bag.\nblue bags contain no other bags.")
+            .contains("line 1, column 31: red bags contain teal bags, which have no rule")
+    );
+    assert!(
+        error("red bags contain 1 blue bag.\nblue bags contain 2 red bags.")
+            .contains("line 1, column 18: red bags end up inside themselves: red -> blue -> red")
+    );
+    assert!(
+        error("red bags contain 1 blue bag, 2 blue bags.\nblue bags contain no other bags.")
+            .contains("line 1, column 30: red bags list blue bags more than once")
+    );
+
+    let errors = error(
+        "red bags contain 1 blue bag, 1 teal bag.
+red bags contain no other bags.
+blue bags contain 1 blue bag.",
+    );
+    let reasons: Vec<&str> = errors
+        .lines()
+        .filter(|line| line.starts_with("day 7"))
+        .collect();
+    assert_eq!(
+        reasons,
+        vec![
+            "day 7, line 2, column 1: red bags have a rule on lines 1, 2",
+            "day 7, line 1, column 30: red bags contain teal bags, which have no rule",
+            "day 7, line 3, column 19: blue bags end up inside themselves: blue -> blue",
+        ]
+    );
 }
 
 #[test]
 fn test_p1() {
-    assert_eq!(p1(include_str!("../inputs/day07.txt")), 274);
+    assert_eq!(Day07::solve_p1(&crate::input::load(7).unwrap()), Ok(274));
 }
 
 #[test]
 fn test_p2() {
-    assert_eq!(p2(include_str!("../inputs/day07.txt")), 158730);
+    assert_eq!(Day07::solve_p2(&crate::input::load(7).unwrap()), Ok(158730));
 }
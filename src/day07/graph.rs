@@ -0,0 +1,483 @@
+//! The bag rules as a graph, from every bag to the bags it directly contains
+//! and back, answering questions about any bag.
+
+use super::BagDependencies;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{self, Display};
+
+/// A problem with the rules that makes queries meaningless.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleError {
+    /// A bag ending up inside itself. `path` starts and ends with that bag,
+    /// and `line` defines its first bag.
+    Cycle { path: Vec<String>, line: usize },
+    /// `colour` is inside `container`, defined on `line`, but has no rule.
+    Undefined {
+        colour: String,
+        container: String,
+        line: usize,
+    },
+    /// `colour` has a rule on each of `lines`.
+    Duplicate { colour: String, lines: Vec<usize> },
+    /// `container`, defined on `line`, lists `colour` more than once.
+    Repeated {
+        colour: String,
+        container: String,
+        line: usize,
+    },
+}
+
+impl RuleError {
+    /// The line to blame: the rule where the problem shows up.
+    pub fn line(&self) -> usize {
+        match self {
+            RuleError::Cycle { line, .. }
+            | RuleError::Undefined { line, .. }
+            | RuleError::Repeated { line, .. } => *line,
+            RuleError::Duplicate { lines, .. } => lines[lines.len() - 1],
+        }
+    }
+}
+
+impl Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuleError::Cycle { path, .. } => {
+                write!(
+                    f,
+                    "{} bags end up inside themselves: {}",
+                    path[0],
+                    path.join(" -> ")
+                )
+            }
+            RuleError::Undefined {
+                colour, container, ..
+            } => write!(
+                f,
+                "{} bags contain {} bags, which have no rule",
+                container, colour
+            ),
+            RuleError::Repeated {
+                colour, container, ..
+            } => write!(f, "{} bags list {} bags more than once", container, colour),
+            RuleError::Duplicate { colour, lines } => {
+                let lines: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+                write!(
+                    f,
+                    "{} bags have a rule on lines {}",
+                    colour,
+                    lines.join(", ")
+                )
+            }
+        }
+    }
+}
+
+pub struct BagGraph {
+    names: Vec<String>,
+    indexes: HashMap<String, usize>,
+    /// Lines of the rules telling what the bag contains.
+    definitions: Vec<Vec<usize>>,
+    /// Bags directly inside each bag, with their amount.
+    contents: Vec<Vec<(usize, usize)>>,
+    /// Bags directly containing each bag.
+    containers: Vec<Vec<usize>>,
+    /// Memoised `total_contents`.
+    totals: RefCell<Vec<Option<usize>>>,
+    /// Memoised `ancestors`, sorted.
+    ancestors: RefCell<HashMap<usize, Vec<usize>>>,
+}
+
+impl BagGraph {
+    /// The graph of `rules`, the first one being on line 1. Bags only
+    /// mentioned inside others are in it too, containing nothing.
+    pub fn new(rules: impl IntoIterator<Item = (String, BagDependencies)>) -> Self {
+        let mut graph = BagGraph {
+            names: vec![],
+            indexes: HashMap::new(),
+            definitions: vec![],
+            contents: vec![],
+            containers: vec![],
+            totals: RefCell::new(vec![]),
+            ancestors: RefCell::new(HashMap::new()),
+        };
+
+        for (i, (name, dependencies)) in rules.into_iter().enumerate() {
+            let bag = graph.insert(name);
+            graph.definitions[bag].push(i + 1);
+            for dependency in dependencies {
+                let inner = graph.insert(dependency.name);
+                graph.contents[bag].push((inner, dependency.amount));
+                graph.containers[inner].push(bag);
+            }
+        }
+        graph.totals = RefCell::new(vec![None; graph.names.len()]);
+        graph
+    }
+
+    fn insert(&mut self, name: String) -> usize {
+        if let Some(&index) = self.indexes.get(&name) {
+            return index;
+        }
+        let index = self.names.len();
+        self.indexes.insert(name.clone(), index);
+        self.names.push(name);
+        self.definitions.push(vec![]);
+        self.contents.push(vec![]);
+        self.containers.push(vec![]);
+        index
+    }
+
+    fn find(&self, bag: &str) -> Option<usize> {
+        self.indexes.get(bag).copied()
+    }
+
+    fn names(&self, indexes: &[usize]) -> Vec<&str> {
+        indexes.iter().map(|&i| self.names[i].as_str()).collect()
+    }
+
+    /// Every bag, in the order rules first mention them.
+    pub fn bags(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(String::as_str)
+    }
+
+    /// Whether a rule tells what `bag` contains.
+    pub fn is_defined(&self, bag: &str) -> bool {
+        self.find(bag)
+            .is_some_and(|i| !self.definitions[i].is_empty())
+    }
+
+    /// Bags directly inside `bag`, with their amount.
+    pub fn contents(&self, bag: &str) -> Vec<(&str, usize)> {
+        self.find(bag)
+            .map(|i| {
+                self.contents[i]
+                    .iter()
+                    .map(|&(inner, amount)| (self.names[inner].as_str(), amount))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Bags directly containing `bag`.
+    pub fn containers(&self, bag: &str) -> Vec<&str> {
+        self.find(bag)
+            .map(|i| self.names(&self.containers[i]))
+            .unwrap_or_default()
+    }
+
+    /// Every bag before the bags inside it, `None` if a bag ends up inside itself.
+    pub fn topological_order(&self) -> Option<Vec<&str>> {
+        let mut containing: Vec<usize> = self.containers.iter().map(Vec::len).collect();
+        let mut queue: VecDeque<usize> = (0..self.names.len())
+            .filter(|&i| containing[i] == 0)
+            .collect();
+        let mut order = vec![];
+
+        while let Some(bag) = queue.pop_front() {
+            order.push(bag);
+            for &(inner, _) in &self.contents[bag] {
+                containing[inner] -= 1;
+                if containing[inner] == 0 {
+                    queue.push_back(inner);
+                }
+            }
+        }
+        match order.len() == self.names.len() {
+            true => Some(self.names(&order)),
+            false => None,
+        }
+    }
+
+    fn ancestor_indexes(&self, bag: usize) -> Vec<usize> {
+        if let Some(ancestors) = self.ancestors.borrow().get(&bag) {
+            return ancestors.clone();
+        }
+
+        let mut seen = vec![false; self.names.len()];
+        let mut stack = vec![bag];
+        while let Some(i) = stack.pop() {
+            for &outer in &self.containers[i] {
+                if !seen[outer] {
+                    seen[outer] = true;
+                    stack.push(outer);
+                }
+            }
+        }
+        let ancestors: Vec<usize> = (0..seen.len()).filter(|&i| seen[i]).collect();
+        self.ancestors.borrow_mut().insert(bag, ancestors.clone());
+        ancestors
+    }
+
+    /// Bags eventually containing `bag`, by name.
+    pub fn ancestors(&self, bag: &str) -> Vec<&str> {
+        let mut ancestors = self
+            .find(bag)
+            .map(|i| self.names(&self.ancestor_indexes(i)))
+            .unwrap_or_default();
+        ancestors.sort_unstable();
+        ancestors
+    }
+
+    /// Bags eventually inside `bag`, by name.
+    pub fn descendants(&self, bag: &str) -> Vec<&str> {
+        let start = match self.find(bag) {
+            Some(start) => start,
+            None => return vec![],
+        };
+        let mut seen = vec![false; self.names.len()];
+        let mut stack = vec![start];
+        while let Some(i) = stack.pop() {
+            for &(inner, _) in &self.contents[i] {
+                if !seen[inner] {
+                    seen[inner] = true;
+                    stack.push(inner);
+                }
+            }
+        }
+        let mut descendants: Vec<&str> = (0..seen.len())
+            .filter(|&i| seen[i])
+            .map(|i| self.names[i].as_str())
+            .collect();
+        descendants.sort_unstable();
+        descendants
+    }
+
+    /// Whether `inner` ends up inside `outer`.
+    pub fn can_contain(&self, outer: &str, inner: &str) -> bool {
+        match (self.find(outer), self.find(inner)) {
+            (Some(outer), Some(inner)) => {
+                self.ancestor_indexes(inner).binary_search(&outer).is_ok()
+            }
+            _ => false,
+        }
+    }
+
+    fn total(&self, bag: usize) -> usize {
+        if let Some(total) = self.totals.borrow()[bag] {
+            return total;
+        }
+        let total = self.contents[bag]
+            .iter()
+            .map(|&(inner, amount)| amount * (1 + self.total(inner)))
+            .sum();
+        self.totals.borrow_mut()[bag] = Some(total);
+        total
+    }
+
+    /// How many bags `bag` holds, all levels together. The rules must have no
+    /// cycle, see [`validate`](Self::validate).
+    pub fn total_contents(&self, bag: &str) -> Option<usize> {
+        self.find(bag).map(|i| self.total(i))
+    }
+
+    /// A path from `from` back to it, when some bag in `path` contains it.
+    fn close_cycle(&self, path: &[usize], from: usize) -> Vec<String> {
+        let start = path.iter().position(|&i| i == from).unwrap_or(0);
+        path[start..]
+            .iter()
+            .chain(Some(&from))
+            .map(|&i| self.names[i].clone())
+            .collect()
+    }
+
+    /// Every cycle found by a depth-first search, one per edge closing it.
+    fn cycles(&self) -> Vec<Vec<String>> {
+        // 0 unvisited, 1 on the current path, 2 done.
+        let mut state = vec![0u8; self.names.len()];
+        let mut cycles = vec![];
+
+        for root in 0..self.names.len() {
+            if state[root] != 0 {
+                continue;
+            }
+            state[root] = 1;
+            let mut path = vec![root];
+            let mut next_child = vec![0];
+
+            while let Some(&bag) = path.last() {
+                let child = next_child.last_mut().unwrap();
+                match self.contents[bag].get(*child) {
+                    Some(&(inner, _)) => {
+                        *child += 1;
+                        match state[inner] {
+                            0 => {
+                                state[inner] = 1;
+                                path.push(inner);
+                                next_child.push(0);
+                            }
+                            1 => cycles.push(self.close_cycle(&path, inner)),
+                            _ => {}
+                        }
+                    }
+                    None => {
+                        state[bag] = 2;
+                        path.pop();
+                        next_child.pop();
+                    }
+                }
+            }
+        }
+        cycles
+    }
+
+    /// Everything wrong with the rules: duplicates, colours listed twice by a
+    /// rule, undefined colours, then cycles.
+    pub fn validate(&self) -> Vec<RuleError> {
+        let mut errors = vec![];
+
+        for (bag, lines) in self.definitions.iter().enumerate() {
+            if lines.len() > 1 {
+                errors.push(RuleError::Duplicate {
+                    colour: self.names[bag].clone(),
+                    lines: lines.clone(),
+                });
+            }
+        }
+        for (bag, contents) in self.contents.iter().enumerate() {
+            for (i, &(inner, _)) in contents.iter().enumerate() {
+                if contents[..i].iter().any(|&(other, _)| other == inner)
+                    && !contents[i + 1..].iter().any(|&(other, _)| other == inner)
+                {
+                    errors.push(RuleError::Repeated {
+                        colour: self.names[inner].clone(),
+                        container: self.names[bag].clone(),
+                        line: self.definitions[bag][0],
+                    });
+                }
+            }
+        }
+        for (bag, lines) in self.definitions.iter().enumerate() {
+            if !lines.is_empty() {
+                continue;
+            }
+            for &container in &self.containers[bag] {
+                errors.push(RuleError::Undefined {
+                    colour: self.names[bag].clone(),
+                    container: self.names[container].clone(),
+                    line: self.definitions[container][0],
+                });
+            }
+        }
+        for path in self.cycles() {
+            let line = self.definitions[self.indexes[&path[0]]][0];
+            errors.push(RuleError::Cycle { path, line });
+        }
+        errors
+    }
+}
+
+#[test]
+fn test_edges() {
+    let graph = super::example();
+    let mut contents = graph.contents("muted yellow");
+    contents.sort_unstable();
+    assert_eq!(contents, vec![("faded blue", 9), ("shiny gold", 2)]);
+    let mut containers = graph.containers("shiny gold");
+    containers.sort_unstable();
+    assert_eq!(containers, vec!["bright white", "muted yellow"]);
+    assert!(graph.contents("teal").is_empty());
+    assert!(graph.is_defined("faded blue"));
+}
+
+#[test]
+fn test_queries() {
+    let graph = super::example();
+    assert_eq!(
+        graph.ancestors("shiny gold"),
+        vec!["bright white", "dark orange", "light red", "muted yellow"]
+    );
+    assert!(graph.ancestors("light red").is_empty());
+    assert_eq!(
+        graph.descendants("shiny gold"),
+        vec!["dark olive", "dotted black", "faded blue", "vibrant plum"]
+    );
+    assert!(graph.can_contain("light red", "dotted black"));
+    assert!(!graph.can_contain("shiny gold", "bright white"));
+    assert!(!graph.can_contain("shiny gold", "teal"));
+
+    assert_eq!(graph.total_contents("shiny gold"), Some(32));
+    assert_eq!(graph.total_contents("dark olive"), Some(7));
+    assert_eq!(graph.total_contents("faded blue"), Some(0));
+    assert_eq!(graph.total_contents("teal"), None);
+}
+
+#[test]
+fn test_topological_order() {
+    let graph = super::example();
+    let order = graph.topological_order().unwrap();
+    assert_eq!(order.len(), 9);
+    let position = |bag| order.iter().position(|&b| b == bag).unwrap();
+    for outer in graph.bags() {
+        for (inner, _) in graph.contents(outer) {
+            assert!(position(outer) < position(inner));
+        }
+    }
+
+    let looping = BagGraph::new(
+        [
+            "red bags contain 1 blue bag.",
+            "blue bags contain 2 red bags.",
+        ]
+        .iter()
+        .map(|line| super::parse_line(line).unwrap()),
+    );
+    assert_eq!(looping.topological_order(), None);
+}
+
+#[test]
+fn test_validate() {
+    let rules = "red bags contain 1 blue bag.
+blue bags contain 2 green bags, 1 teal bag.
+green bags contain 3 red bags.
+white bags contain no other bags.
+white bags contain 1 white bag.";
+    let graph = BagGraph::new(rules.lines().map(|line| super::parse_line(line).unwrap()));
+    let errors = graph.validate();
+    assert_eq!(
+        errors,
+        vec![
+            RuleError::Duplicate {
+                colour: String::from("white"),
+                lines: vec![4, 5]
+            },
+            RuleError::Undefined {
+                colour: String::from("teal"),
+                container: String::from("blue"),
+                line: 2
+            },
+            RuleError::Cycle {
+                path: vec![
+                    String::from("red"),
+                    String::from("blue"),
+                    String::from("green"),
+                    String::from("red")
+                ],
+                line: 1
+            },
+            RuleError::Cycle {
+                path: vec![String::from("white"), String::from("white")],
+                line: 4
+            },
+        ]
+    );
+    assert_eq!(
+        errors[2].to_string(),
+        "red bags end up inside themselves: red -> blue -> green -> red"
+    );
+    assert!(super::example().validate().is_empty());
+
+    let rules = "red bags contain 1 blue bag, 2 blue bags.
+blue bags contain no other bags.";
+    let graph = BagGraph::new(rules.lines().map(|line| super::parse_line(line).unwrap()));
+    assert_eq!(
+        graph.validate(),
+        vec![RuleError::Repeated {
+            colour: String::from("blue"),
+            container: String::from("red"),
+            line: 1
+        }]
+    );
+    assert_eq!(graph.total_contents("red"), Some(3));
+}
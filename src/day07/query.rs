@@ -0,0 +1,160 @@
+//! Questions about a bag needing more than a count: what exactly it holds,
+//! how deep, and which bags it ends up in at the outermost.
+
+use super::graph::BagGraph;
+use std::collections::{BTreeMap, HashMap};
+
+/// Everything a bag holds, all levels together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inventory<'g> {
+    /// How many bags of each colour, at any level.
+    pub bags: BTreeMap<&'g str, usize>,
+    /// Fewest levels of bags down to one holding nothing, 0 for an empty bag.
+    pub min_depth: usize,
+    /// Most levels of bags down to one holding nothing.
+    pub max_depth: usize,
+    /// Colours of the bags inside holding nothing, by name.
+    pub leaves: Vec<&'g str>,
+}
+
+impl Inventory<'_> {
+    pub fn total(&self) -> usize {
+        self.bags.values().sum()
+    }
+}
+
+/// The depth of `bag` in both directions, memoised in `depths`.
+fn depth<'g>(
+    graph: &'g BagGraph,
+    bag: &'g str,
+    depths: &mut HashMap<&'g str, (usize, usize)>,
+) -> (usize, usize) {
+    if let Some(&depth) = depths.get(bag) {
+        return depth;
+    }
+    let mut levels: Option<(usize, usize)> = None;
+    for (inner, _) in graph.contents(bag) {
+        let (min, max) = depth(graph, inner, depths);
+        levels = Some(match levels {
+            None => (min + 1, max + 1),
+            Some((lowest, highest)) => (lowest.min(min + 1), highest.max(max + 1)),
+        });
+    }
+    let levels = levels.unwrap_or((0, 0));
+    depths.insert(bag, levels);
+    levels
+}
+
+/// What `bag` holds, `None` if no rule mentions it or the rules have a cycle.
+pub fn inventory<'g>(graph: &'g BagGraph, bag: &str) -> Option<Inventory<'g>> {
+    let order = graph.topological_order()?;
+    let position = order.iter().position(|&b| b == bag)?;
+
+    // Bags come before the ones inside them, so every count is final when reached.
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    counts.insert(order[position], 1);
+    for &outer in &order[position..] {
+        let count = match counts.get(outer) {
+            Some(&count) => count,
+            None => continue,
+        };
+        for (inner, amount) in graph.contents(outer) {
+            *counts.entry(inner).or_insert(0) += count * amount;
+        }
+    }
+    counts.remove(order[position]);
+
+    let mut leaves: Vec<&str> = counts
+        .keys()
+        .copied()
+        .filter(|inner| graph.contents(inner).is_empty())
+        .collect();
+    leaves.sort_unstable();
+    let (min_depth, max_depth) = depth(graph, order[position], &mut HashMap::new());
+
+    Some(Inventory {
+        bags: counts.into_iter().collect(),
+        min_depth,
+        max_depth,
+        leaves,
+    })
+}
+
+/// Bags containing `bag` that no other bag contains, with how many distinct
+/// chains of bags lead from each down to `bag`. Sorted by name, empty if no
+/// rule mentions it or the rules have a cycle.
+pub fn outermost<'g>(graph: &'g BagGraph, bag: &str) -> Vec<(&'g str, usize)> {
+    let order = match graph.topological_order() {
+        Some(order) => order,
+        None => return vec![],
+    };
+    let position = match order.iter().position(|&b| b == bag) {
+        Some(position) => position,
+        None => return vec![],
+    };
+
+    // Going backwards, every bag comes after the ones inside it.
+    let mut paths: HashMap<&str, usize> = HashMap::new();
+    paths.insert(order[position], 1);
+    for &outer in order[..position].iter().rev() {
+        let count = graph
+            .contents(outer)
+            .iter()
+            .map(|(inner, _)| paths.get(inner).copied().unwrap_or(0))
+            .sum();
+        if count > 0 {
+            paths.insert(outer, count);
+        }
+    }
+
+    let mut outermost: Vec<(&str, usize)> = paths
+        .into_iter()
+        .filter(|&(outer, _)| outer != bag && graph.containers(outer).is_empty())
+        .collect();
+    outermost.sort_unstable();
+    outermost
+}
+
+#[test]
+fn test_inventory() {
+    let graph = super::example();
+    let inventory = inventory(&graph, "shiny gold").unwrap();
+    assert_eq!(
+        inventory.bags.into_iter().collect::<Vec<_>>(),
+        vec![
+            ("dark olive", 1),
+            ("dotted black", 16),
+            ("faded blue", 13),
+            ("vibrant plum", 2)
+        ]
+    );
+    assert_eq!((inventory.min_depth, inventory.max_depth), (2, 2));
+    assert_eq!(inventory.leaves, vec!["dotted black", "faded blue"]);
+
+    let inventory = self::inventory(&graph, "muted yellow").unwrap();
+    assert_eq!(inventory.total(), 2 * 33 + 9);
+    assert_eq!((inventory.min_depth, inventory.max_depth), (1, 3));
+
+    let empty = self::inventory(&graph, "faded blue").unwrap();
+    assert!(empty.bags.is_empty() && empty.leaves.is_empty());
+    assert_eq!(empty.max_depth, 0);
+    assert_eq!(self::inventory(&graph, "teal"), None);
+
+    let graph = super::build_bag_graph(&crate::input::load(7).unwrap()).unwrap();
+    let inventory = self::inventory(&graph, super::MY_BAG).unwrap();
+    assert_eq!(inventory.total(), 158730);
+}
+
+#[test]
+fn test_outermost() {
+    let graph = super::example();
+    assert_eq!(
+        outermost(&graph, "shiny gold"),
+        vec![("dark orange", 2), ("light red", 2)]
+    );
+    assert_eq!(
+        outermost(&graph, "faded blue"),
+        vec![("dark orange", 5), ("light red", 5)]
+    );
+    assert!(outermost(&graph, "light red").is_empty());
+}
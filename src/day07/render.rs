@@ -0,0 +1,162 @@
+//! Drawing bag rules in Graphviz DOT or Mermaid, an edge from every bag to
+//! the bags it contains, labelled with their amount.
+
+use super::graph::BagGraph;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Which bags to draw.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter<'a> {
+    All,
+    /// The bag and every bag containing it.
+    Ancestors(&'a str),
+    /// The bag and every bag inside it.
+    Descendants(&'a str),
+}
+
+/// Bags to draw, sorted by name, and the edges between them as indexes in
+/// that order, which the nodes are named after since colours can hold any
+/// character.
+fn selection<'g>(
+    graph: &'g BagGraph,
+    filter: Filter,
+) -> (Vec<&'g str>, Vec<(usize, usize, usize)>) {
+    let mut bags: Vec<&str> = match filter {
+        Filter::All => graph.bags().collect(),
+        Filter::Ancestors(bag) => graph.ancestors(bag),
+        Filter::Descendants(bag) => graph.descendants(bag),
+    };
+    if let Filter::Ancestors(bag) | Filter::Descendants(bag) = filter {
+        bags.extend(graph.bags().find(|&b| b == bag));
+    }
+    bags.sort_unstable();
+
+    let ids: HashMap<&str, usize> = bags.iter().enumerate().map(|(i, &bag)| (bag, i)).collect();
+    let mut edges = vec![];
+    for (outer, &bag) in bags.iter().enumerate() {
+        let mut contents = graph.contents(bag);
+        contents.sort_unstable();
+        for (inner, amount) in contents {
+            if let Some(&inner) = ids.get(inner) {
+                edges.push((outer, inner, amount));
+            }
+        }
+    }
+    (bags, edges)
+}
+
+/// `text` between double quotes in DOT.
+fn dot_label(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// The bags kept by `filter`, `highlight` filled in gold.
+pub fn dot(graph: &BagGraph, filter: Filter, highlight: Option<&str>) -> String {
+    let (bags, edges) = selection(graph, filter);
+    let mut dot = String::from("digraph bags {\n    node [shape=box, style=rounded];\n");
+    for (id, &bag) in bags.iter().enumerate() {
+        write!(dot, "    n{} [label={}", id, dot_label(bag)).unwrap();
+        if Some(bag) == highlight {
+            dot.push_str(", style=\"rounded,filled,bold\", fillcolor=gold");
+        }
+        dot.push_str("];\n");
+    }
+    for (outer, inner, amount) in edges {
+        writeln!(dot, "    n{} -> n{} [label=\"{}\"];", outer, inner, amount).unwrap();
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// `text` between double quotes in Mermaid, which has entities rather than
+/// escapes.
+fn mermaid_label(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "#quot;"))
+}
+
+/// Like [`dot`], as a Mermaid flowchart.
+pub fn mermaid(graph: &BagGraph, filter: Filter, highlight: Option<&str>) -> String {
+    let (bags, edges) = selection(graph, filter);
+    let mut mermaid = String::from("flowchart TD\n");
+    for (id, &bag) in bags.iter().enumerate() {
+        writeln!(mermaid, "    n{}[{}]", id, mermaid_label(bag)).unwrap();
+    }
+    for (outer, inner, amount) in edges {
+        writeln!(mermaid, "    n{} -->|{}| n{}", outer, amount, inner).unwrap();
+    }
+    if let Some(id) = highlight.and_then(|bag| bags.iter().position(|&b| b == bag)) {
+        mermaid.push_str("    classDef query fill:#ffd700,stroke:#333,stroke-width:2px\n");
+        writeln!(mermaid, "    class n{} query", id).unwrap();
+    }
+    mermaid
+}
+
+#[test]
+fn test_dot() {
+    let graph = super::example();
+    let dot = dot(&graph, Filter::All, Some("shiny gold"));
+    assert!(dot.starts_with("digraph bags {"));
+    assert!(dot.contains(
+        "    n7 [label=\"shiny gold\", style=\"rounded,filled,bold\", fillcolor=gold];\n"
+    ));
+    assert!(dot.contains("    n5 [label=\"light red\"];\n"));
+    // From muted yellow to faded blue.
+    assert!(dot.contains("    n6 -> n4 [label=\"9\"];\n"));
+    assert_eq!(dot.matches("->").count(), 13);
+}
+
+#[test]
+fn test_filters() {
+    let graph = super::example();
+    let ancestors = dot(&graph, Filter::Ancestors("shiny gold"), None);
+    assert!(ancestors.contains("n2 [label=\"light red\"];"));
+    assert!(ancestors.contains("n2 -> n0 [label=\"1\"];"));
+    assert!(!ancestors.contains("faded blue"));
+    assert_eq!(ancestors.matches("->").count(), 6);
+
+    let descendants = mermaid(
+        &graph,
+        Filter::Descendants("shiny gold"),
+        Some("shiny gold"),
+    );
+    assert_eq!(
+        descendants,
+        "flowchart TD
+    n0[\"dark olive\"]
+    n1[\"dotted black\"]
+    n2[\"faded blue\"]
+    n3[\"shiny gold\"]
+    n4[\"vibrant plum\"]
+    n0 -->|4| n1
+    n0 -->|3| n2
+    n3 -->|1| n0
+    n3 -->|2| n4
+    n4 -->|6| n1
+    n4 -->|5| n2
+    classDef query fill:#ffd700,stroke:#333,stroke-width:2px
+    class n3 query
+"
+    );
+}
+
+#[test]
+fn test_awkward_colours() {
+    let graph = BagGraph::new(
+        [
+            "dark-olive bags contain 1 dark olive bag.",
+            "dark olive bags contain 2 say \"hi\\ bags.",
+            "say \"hi\\ bags contain no other bags.",
+        ]
+        .iter()
+        .map(|line| super::parse_line(line).unwrap()),
+    );
+    let dot = dot(&graph, Filter::All, None);
+    assert!(dot.contains("    n0 [label=\"dark olive\"];\n    n1 [label=\"dark-olive\"];\n"));
+    assert!(dot.contains("    n2 [label=\"say \\\"hi\\\\\"];\n"));
+    assert!(dot.contains("    n0 -> n2 [label=\"2\"];\n    n1 -> n0 [label=\"1\"];\n"));
+
+    let mermaid = mermaid(&graph, Filter::All, None);
+    assert!(mermaid.contains("    n2[\"say #quot;hi\\\"]\n"));
+    assert!(mermaid.contains("    n1 -->|1| n0\n"));
+}
@@ -1,117 +1,143 @@
-use std::collections::HashSet;
-
-#[derive(Clone)]
-enum Instruction {
+use crate::error::Malformed;
+use crate::parse::{number, parse_lines};
+use crate::{Error, Part, Result, Solution};
+use machine::{Machine, Termination};
+use repair::repair;
+use std::fmt::{self, Display};
+
+pub mod analysis;
+pub mod asm;
+pub mod debugger;
+pub mod machine;
+pub mod repair;
+pub mod trace;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Instruction {
     Acc(isize),
     Jmp(isize),
     Nop(isize),
 }
 
-enum ProgramError {
-    InfiniteLoop,
-}
-
-type Program = Vec<Instruction>;
-type ProgramProcessing<'a> = Result<&'a Instruction, &'a ProgramError>;
-
-pub fn p1(input: &str) -> isize {
-    run(&parse(input)).0
-}
-
-pub fn p2(input: &str) -> isize {
-    let mut program = parse(input);
+impl Instruction {
+    pub fn opcode(&self) -> &'static str {
+        match self {
+            Instruction::Acc(_) => "acc",
+            Instruction::Jmp(_) => "jmp",
+            Instruction::Nop(_) => "nop",
+        }
+    }
 
-    fn swap_instructions(program: &Program, index: usize) -> Instruction {
-        match program[index] {
-            Instruction::Nop(val) => Instruction::Jmp(val),
-            Instruction::Jmp(val) => Instruction::Nop(val),
-            Instruction::Acc(val) => Instruction::Acc(val),
+    /// The instruction with `jmp` and `nop` swapped, `acc` having no swap.
+    pub fn flipped(&self) -> Option<Instruction> {
+        match *self {
+            Instruction::Acc(_) => None,
+            Instruction::Jmp(val) => Some(Instruction::Nop(val)),
+            Instruction::Nop(val) => Some(Instruction::Jmp(val)),
         }
     }
 
-    program
-        .clone()
-        .iter()
-        .enumerate()
-        .filter(|(_, instruction)| matches!(instruction, Instruction::Nop(_) | Instruction::Jmp(_)))
-        .find_map(|(i, _)| {
-            program[i] = swap_instructions(&program, i);
-            let (res, bugged) = run(&program);
-            program[i] = swap_instructions(&program, i);
-
-            match bugged {
-                true => None,
-                false => Some(res),
-            }
-        })
-        .unwrap()
+    pub fn argument(&self) -> isize {
+        match *self {
+            Instruction::Acc(val) | Instruction::Jmp(val) | Instruction::Nop(val) => val,
+        }
+    }
 }
 
-fn parse(input: &str) -> Program {
-    input.lines().map(parse_instruction).collect()
+impl Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(&format!("{} {:+}", self.opcode(), self.argument()))
+    }
 }
 
-fn parse_instruction(line: &str) -> Instruction {
-    let (instruction_type, number) = line.split_once(" ").unwrap();
+pub static OPCODES: [&str; 3] = ["acc", "jmp", "nop"];
 
-    match instruction_type {
-        "acc" => Instruction::Acc(number.parse::<isize>().unwrap()),
-        "jmp" => Instruction::Jmp(number.parse::<isize>().unwrap()),
-        "nop" => Instruction::Nop(number.parse::<isize>().unwrap()),
-        _ => unreachable!(),
+type Program = Vec<Instruction>;
+
+pub struct Day08;
+
+impl Solution for Day08 {
+    const DAY: u8 = 8;
+    type Input = Program;
+    type Answer = isize;
+
+    fn parse(input: &str) -> Result<Program> {
+        parse_lines(Self::DAY, input, parse_instruction)
+    }
+
+    fn p1(program: &Program) -> Result<isize> {
+        Ok(Machine::new(program).run().acc())
+    }
+
+    fn p2(program: &Program) -> Result<isize> {
+        repair(program)
+            .map(|repair| repair.acc)
+            .map_err(|e| Error::no_answer(Self::DAY, Part::Two, e.to_string()))
     }
 }
 
-fn run(program: &Program) -> (isize, bool) {
-    let mut bugged = false;
+/// Swaps every `jmp` and `nop` in turn, running the whole program each time.
+pub fn p2_brute_force(program: &[Instruction]) -> Result<isize> {
+    let mut program = program.to_vec();
 
-    let res = interpreter(program)
-        .take_while(|instruction| {
-            if Result::is_ok(instruction) {
-                true
-            } else {
-                bugged = true;
-                false
+    (0..program.len())
+        .find_map(|i| {
+            let flipped = program[i].flipped()?;
+            let original = std::mem::replace(&mut program[i], flipped);
+            let termination = Machine::new(&program).run();
+            program[i] = original;
+
+            match termination {
+                Termination::Exit { acc, .. } => Some(acc),
+                _ => None,
             }
         })
-        .filter_map(|instruction| match instruction.ok().unwrap() {
-            Instruction::Acc(val) => Some(val),
-            _ => None,
+        .ok_or_else(|| {
+            Error::no_answer(
+                Day08::DAY,
+                Part::Two,
+                "no single jmp/nop swap makes the program terminate",
+            )
         })
-        .sum();
-
-    (res, bugged)
 }
 
-fn interpreter<'a>(program: &'a Program) -> impl Iterator<Item = ProgramProcessing> + 'a {
-    let mut i: isize = 0;
-    let mut executed_instruction_indexes: HashSet<usize> = HashSet::new();
+fn parse_instruction(line: &str) -> std::result::Result<Instruction, Malformed> {
+    let (instruction_type, val) = line
+        .split_once(" ")
+        .ok_or_else(|| Malformed::new(1, "expected `{operation} {argument}`"))?;
 
-    std::iter::from_fn(move || {
-        if i as usize >= program.len() {
-            return None;
-        }
-        if executed_instruction_indexes.contains(&(i as usize)) {
-            return Some(Err(&ProgramError::InfiniteLoop));
-        }
-        executed_instruction_indexes.insert(i as usize);
-
-        let instruction = &program[i as usize];
-        match instruction {
-            Instruction::Jmp(val) => i += val,
-            _ => i += 1,
-        };
+    match instruction_type {
+        "acc" => Ok(Instruction::Acc(number(line, val)?)),
+        "jmp" => Ok(Instruction::Jmp(number(line, val)?)),
+        "nop" => Ok(Instruction::Nop(number(line, val)?)),
+        _ => Err(Malformed::new(
+            1,
+            format!("unknown operation `{}`", instruction_type),
+        )),
+    }
+}
 
-        Some(Ok(instruction))
-    })
+/// The example program of the puzzle.
+#[cfg(test)]
+fn example() -> Program {
+    Day08::parse("nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6").unwrap()
 }
 
 #[test]
 fn test_p1() {
-    assert_eq!(p1(include_str!("../inputs/day08.txt")), 1563);
+    assert_eq!(Day08::solve_p1(&crate::input::load(8).unwrap()), Ok(1563));
+}
+
+#[test]
+fn test_p2_brute_force() {
+    use Instruction::*;
+
+    // Swapping the nop jumps past the end, which isn't terminating.
+    assert_eq!(p2_brute_force(&[Nop(5), Acc(1), Jmp(-1)]), Ok(1));
+    assert_eq!(p2_brute_force(&example()), Ok(8));
 }
 
 #[test]
 fn test_p2() {
-    assert_eq!(p2(include_str!("../inputs/day08.txt")), 767);
+    assert_eq!(Day08::solve_p2(&crate::input::load(8).unwrap()), Ok(767));
 }
@@ -0,0 +1,269 @@
+//! Static analysis of day 8 programs: what can be told without running them.
+
+use super::repair::successor;
+use super::Instruction;
+use std::fmt::{self, Display};
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Diagnostic {
+    /// Instructions no path from the start goes through.
+    DeadCode { indexes: Range<usize> },
+    /// A `jmp` to neither an instruction nor right after the last one.
+    JumpOutOfBounds { index: usize, target: isize },
+    /// A `jmp +0`.
+    SelfLoop { index: usize },
+    /// Instructions that, once reached, run forever.
+    InfiniteLoop {
+        indexes: Vec<usize>,
+        reachable: bool,
+    },
+}
+
+impl Diagnostic {
+    /// The first instruction concerned, to sort diagnostics.
+    fn index(&self) -> usize {
+        match self {
+            Diagnostic::DeadCode { indexes } => indexes.start,
+            Diagnostic::JumpOutOfBounds { index, .. } | Diagnostic::SelfLoop { index } => *index,
+            Diagnostic::InfiniteLoop { indexes, .. } => indexes[0],
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Diagnostic::DeadCode { indexes } if indexes.len() == 1 => {
+                write!(f, "instruction {} can never run", indexes.start)
+            }
+            Diagnostic::DeadCode { indexes } => write!(
+                f,
+                "instructions {} to {} can never run",
+                indexes.start,
+                indexes.end - 1
+            ),
+            Diagnostic::JumpOutOfBounds { index, target } => write!(
+                f,
+                "instruction {} jumps to {}, outside of the program",
+                index, target
+            ),
+            Diagnostic::SelfLoop { index } => write!(f, "instruction {} jumps to itself", index),
+            Diagnostic::InfiniteLoop { indexes, reachable } => {
+                let indexes: Vec<String> = indexes.iter().map(|i| i.to_string()).collect();
+                write!(
+                    f,
+                    "instructions {} loop forever, {}",
+                    indexes.join(", "),
+                    match reachable {
+                        true => "and the program gets there",
+                        false => "but the program never gets there",
+                    }
+                )
+            }
+        }
+    }
+}
+
+/// Instructions that can run right after the one at each index.
+fn successors(program: &[Instruction]) -> Vec<Vec<usize>> {
+    let len = program.len();
+    program
+        .iter()
+        .enumerate()
+        .map(|(i, instruction)| {
+            successor(i, instruction, len)
+                .filter(|&next| next < len)
+                .into_iter()
+                .collect()
+        })
+        .collect()
+}
+
+fn reachable(successors: &[Vec<usize>]) -> Vec<bool> {
+    let mut reached = vec![false; successors.len()];
+    let mut stack = vec![];
+    if !successors.is_empty() {
+        reached[0] = true;
+        stack.push(0);
+    }
+    while let Some(i) = stack.pop() {
+        for &next in &successors[i] {
+            if !reached[next] {
+                reached[next] = true;
+                stack.push(next);
+            }
+        }
+    }
+    reached
+}
+
+/// Strongly connected components, by Tarjan's algorithm without recursion.
+fn components(successors: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let n = successors.len();
+    let mut order = vec![None; n];
+    let mut low = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = vec![];
+    let mut components = vec![];
+    let mut counter = 0;
+
+    for root in 0..n {
+        if order[root].is_some() {
+            continue;
+        }
+        let mut work = vec![(root, 0)];
+        order[root] = Some(counter);
+        low[root] = counter;
+        counter += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some(&mut (node, ref mut child)) = work.last_mut() {
+            if let Some(&next) = successors[node].get(*child) {
+                *child += 1;
+                match order[next] {
+                    None => {
+                        order[next] = Some(counter);
+                        low[next] = counter;
+                        counter += 1;
+                        stack.push(next);
+                        on_stack[next] = true;
+                        work.push((next, 0));
+                    }
+                    Some(o) if on_stack[next] => low[node] = low[node].min(o),
+                    Some(_) => {}
+                }
+                continue;
+            }
+
+            work.pop();
+            if let Some(&(parent, _)) = work.last() {
+                low[parent] = low[parent].min(low[node]);
+            }
+            if Some(low[node]) == order[node] {
+                let mut component = vec![];
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                component.sort_unstable();
+                components.push(component);
+            }
+        }
+    }
+    components
+}
+
+/// Everything suspicious in `program`, ordered by instruction.
+pub fn analyse(program: &[Instruction]) -> Vec<Diagnostic> {
+    let successors = successors(program);
+    let reached = reachable(&successors);
+    let mut diagnostics = vec![];
+
+    let mut i = 0;
+    while i < program.len() {
+        if reached[i] {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < program.len() && !reached[i] {
+            i += 1;
+        }
+        diagnostics.push(Diagnostic::DeadCode { indexes: start..i });
+    }
+
+    for (i, instruction) in program.iter().enumerate() {
+        match *instruction {
+            Instruction::Jmp(0) => diagnostics.push(Diagnostic::SelfLoop { index: i }),
+            Instruction::Jmp(offset) => {
+                let target = i as isize + offset;
+                if target < 0 || target > program.len() as isize {
+                    diagnostics.push(Diagnostic::JumpOutOfBounds { index: i, target });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for component in components(&successors) {
+        let cyclic = component.len() > 1 || successors[component[0]].contains(&component[0]);
+        let exits = component
+            .iter()
+            .flat_map(|&i| &successors[i])
+            .any(|next| component.binary_search(next).is_err());
+        // Whether a self-loop can be left doesn't matter, it never is.
+        let self_loop = component.len() == 1 && cyclic;
+        if cyclic && !self_loop && !exits {
+            diagnostics.push(Diagnostic::InfiniteLoop {
+                reachable: reached[component[0]],
+                indexes: component,
+            });
+        }
+    }
+
+    diagnostics.sort_by_key(Diagnostic::index);
+    diagnostics
+}
+
+#[test]
+fn test_example() {
+    assert_eq!(
+        analyse(&super::example()),
+        vec![
+            Diagnostic::InfiniteLoop {
+                indexes: vec![1, 2, 3, 4, 6, 7],
+                reachable: true
+            },
+            Diagnostic::DeadCode { indexes: 5..6 },
+            Diagnostic::DeadCode { indexes: 8..9 },
+        ]
+    );
+}
+
+#[test]
+fn test_diagnostics() {
+    use Instruction::*;
+
+    let program = [
+        Jmp(2),
+        Jmp(0),
+        Acc(1),
+        Jmp(3),
+        Nop(0),
+        Jmp(-1),
+        Jmp(-9),
+        Acc(3),
+    ];
+    let diagnostics = analyse(&program);
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::DeadCode { indexes: 1..2 },
+            Diagnostic::SelfLoop { index: 1 },
+            Diagnostic::DeadCode { indexes: 4..6 },
+            Diagnostic::InfiniteLoop {
+                indexes: vec![4, 5],
+                reachable: false
+            },
+            Diagnostic::JumpOutOfBounds {
+                index: 6,
+                target: -3
+            },
+            Diagnostic::DeadCode { indexes: 7..8 },
+        ]
+    );
+    assert_eq!(
+        diagnostics[2].to_string(),
+        "instructions 4 to 5 can never run"
+    );
+    assert_eq!(
+        diagnostics[3].to_string(),
+        "instructions 4, 5 loop forever, but the program never gets there"
+    );
+    assert!(analyse(&[Acc(1), Jmp(1), Nop(0)]).is_empty());
+}
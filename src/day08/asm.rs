@@ -0,0 +1,350 @@
+//! An assembly language for day 8 programs, and a compact binary encoding.
+//!
+//! The assembly is the puzzle format extended with comments, starting with
+//! `#` or `;`, and labels, which `jmp` and `nop` can use instead of offsets:
+//!
+//! ```text
+//! loop_start:
+//!     acc +1      # count
+//!     jmp loop_start
+//! ```
+
+use super::{Day08, Instruction, Program};
+use crate::error::Malformed;
+use crate::parse::number;
+use crate::{Result, Solution};
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+enum Argument<'a> {
+    Offset(isize),
+    Label(&'a str),
+}
+
+struct Line<'a> {
+    number: usize,
+    text: &'a str,
+    opcode: &'a str,
+    argument: Argument<'a>,
+}
+
+fn is_label(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Turns assembly into a program, labels becoming relative offsets.
+pub fn assemble(source: &str) -> Result<Program> {
+    let mut labels: HashMap<&str, usize> = HashMap::new();
+    let mut lines = vec![];
+
+    for (i, text) in source.lines().enumerate() {
+        let malformed = |e: Malformed| e.into_error(Day08::DAY, i + 1, text);
+        let mut code = text.split(['#', ';']).next().unwrap_or("").trim();
+
+        if let Some((label, rest)) = code.split_once(':') {
+            let label = label.trim();
+            if !is_label(label) {
+                return Err(malformed(Malformed::at(
+                    text,
+                    label,
+                    format!("`{}` is not a valid label", label),
+                )));
+            }
+            if labels.insert(label, lines.len()).is_some() {
+                return Err(malformed(Malformed::at(
+                    text,
+                    label,
+                    format!("label `{}` is already defined", label),
+                )));
+            }
+            code = rest.trim();
+        }
+        if code.is_empty() {
+            continue;
+        }
+
+        let words: Vec<&str> = code.split_whitespace().collect();
+        let (opcode, argument) = match words[..] {
+            [opcode, argument] => (opcode, argument),
+            _ => {
+                return Err(malformed(Malformed::at(
+                    text,
+                    code,
+                    "expected `{operation} {argument}`",
+                )))
+            }
+        };
+        let argument = match argument.chars().next() {
+            Some('+') | Some('-') | Some('0'..='9') => {
+                Argument::Offset(number(text, argument).map_err(malformed)?)
+            }
+            _ if opcode == "acc" => {
+                return Err(malformed(Malformed::at(
+                    text,
+                    argument,
+                    "acc needs a number, not a label",
+                )))
+            }
+            _ => Argument::Label(argument),
+        };
+        lines.push(Line {
+            number: i + 1,
+            text,
+            opcode,
+            argument,
+        });
+    }
+
+    lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            let malformed = |e: Malformed| e.into_error(Day08::DAY, line.number, line.text);
+            let argument = match line.argument {
+                Argument::Offset(offset) => offset,
+                Argument::Label(label) => match labels.get(label) {
+                    Some(&target) => target as isize - index as isize,
+                    None => {
+                        return Err(malformed(Malformed::at(
+                            line.text,
+                            label,
+                            format!("unknown label `{}`", label),
+                        )))
+                    }
+                },
+            };
+            match line.opcode {
+                "acc" => Ok(Instruction::Acc(argument)),
+                "jmp" => Ok(Instruction::Jmp(argument)),
+                "nop" => Ok(Instruction::Nop(argument)),
+                opcode => Err(malformed(Malformed::at(
+                    line.text,
+                    opcode,
+                    format!("unknown operation `{}`", opcode),
+                ))),
+            }
+        })
+        .collect()
+}
+
+/// Assembly for `program`, with a label on every instruction a `jmp` goes to.
+pub fn disassemble(program: &[Instruction]) -> String {
+    let len = program.len() as isize;
+    let target = |index: usize, instruction: &Instruction| match instruction {
+        Instruction::Jmp(offset) => Some(index as isize + offset).filter(|t| (0..=len).contains(t)),
+        _ => None,
+    };
+    let label = |target: isize| match target {
+        t if t == len => String::from("end"),
+        t => format!("l{}", t),
+    };
+
+    let mut targets = vec![false; program.len() + 1];
+    for (i, instruction) in program.iter().enumerate() {
+        if let Some(t) = target(i, instruction) {
+            targets[t as usize] = true;
+        }
+    }
+
+    let mut source = vec![];
+    for (i, instruction) in program.iter().enumerate() {
+        if targets[i] {
+            source.push(format!("{}:", label(i as isize)));
+        }
+        match target(i, instruction) {
+            Some(t) => source.push(format!("    {} {}", instruction.opcode(), label(t))),
+            None => source.push(format!("    {}", instruction)),
+        }
+    }
+    if targets[program.len()] {
+        source.push(format!("{}:", label(len)));
+    }
+    source.join("\n")
+}
+
+static MAGIC: &[u8] = b"HHC\x01";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    BadMagic,
+    Truncated,
+    Overflow { offset: usize },
+    UnknownOpcode { offset: usize, opcode: u64 },
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::BadMagic => write!(f, "not an encoded program"),
+            DecodeError::Truncated => write!(f, "the encoded program is truncated"),
+            DecodeError::Overflow { offset } => {
+                write!(f, "byte {}: number too large", offset)
+            }
+            DecodeError::UnknownOpcode { offset, opcode } => {
+                write!(f, "byte {}: unknown opcode {}", offset, opcode)
+            }
+        }
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u128) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_varint(bytes: &[u8], offset: &mut usize) -> std::result::Result<u128, DecodeError> {
+    let start = *offset;
+    let mut value = 0u128;
+    for shift in (0..128).step_by(7) {
+        let byte = *bytes.get(*offset).ok_or(DecodeError::Truncated)?;
+        *offset += 1;
+        value |= ((byte & 0x7f) as u128)
+            .checked_shl(shift)
+            .filter(|v| v >> shift == (byte & 0x7f) as u128)
+            .ok_or(DecodeError::Overflow { offset: start })?;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(DecodeError::Overflow { offset: start })
+}
+
+/// A header, the number of instructions, then one varint per instruction
+/// holding the opcode in its 2 low bits and the zigzag-encoded argument above.
+pub fn encode(program: &[Instruction]) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    write_varint(&mut bytes, program.len() as u128);
+    for instruction in program {
+        let opcode = match instruction {
+            Instruction::Acc(_) => 0,
+            Instruction::Jmp(_) => 1,
+            Instruction::Nop(_) => 2,
+        };
+        let argument = instruction.argument() as i64;
+        let zigzag = ((argument << 1) ^ (argument >> 63)) as u64 as u128;
+        write_varint(&mut bytes, zigzag << 2 | opcode);
+    }
+    bytes
+}
+
+pub fn decode(bytes: &[u8]) -> std::result::Result<Program, DecodeError> {
+    if !bytes.starts_with(MAGIC) {
+        return Err(DecodeError::BadMagic);
+    }
+    let mut offset = MAGIC.len();
+    let len = read_varint(bytes, &mut offset)?;
+    let too_large = |value: u128, limit: u32, offset: usize| match value >> limit {
+        0 => Ok(value),
+        _ => Err(DecodeError::Overflow { offset }),
+    };
+    let len = too_large(len, 64, MAGIC.len())? as u64;
+
+    let mut program = vec![];
+    for _ in 0..len {
+        let start = offset;
+        let value = too_large(read_varint(bytes, &mut offset)?, 66, start)?;
+        let zigzag = (value >> 2) as u64;
+        let argument = ((zigzag >> 1) as i64 ^ -((zigzag & 1) as i64)) as isize;
+        program.push(match (value & 3) as u64 {
+            0 => Instruction::Acc(argument),
+            1 => Instruction::Jmp(argument),
+            2 => Instruction::Nop(argument),
+            opcode => {
+                return Err(DecodeError::UnknownOpcode {
+                    offset: start,
+                    opcode,
+                })
+            }
+        });
+    }
+    Ok(program)
+}
+
+#[test]
+fn test_assemble() {
+    let source = "# counts to 3
+start:
+    acc +1      ; one more
+    nop start
+    jmp end
+    acc -99
+end: acc +2";
+    assert_eq!(
+        assemble(source),
+        Ok(vec![
+            Instruction::Acc(1),
+            Instruction::Nop(-1),
+            Instruction::Jmp(2),
+            Instruction::Acc(-99),
+            Instruction::Acc(2)
+        ])
+    );
+    assert_eq!(assemble("acc +1\njmp -1"), Day08::parse("acc +1\njmp -1"));
+
+    let error = |source| assemble(source).unwrap_err().to_string();
+    assert!(error("jmp nowhere").contains("line 1, column 5: unknown label `nowhere`"));
+    assert!(error("a:\na: nop +0").contains("line 2, column 1: label `a` is already defined"));
+    assert!(error("acc start").contains("acc needs a number"));
+    assert!(error("mul +2").contains("unknown operation `mul`"));
+    assert!(error("1x: nop +0").contains("`1x` is not a valid label"));
+}
+
+#[test]
+fn test_disassemble() {
+    let program = super::example();
+    let source = disassemble(&program);
+    assert_eq!(
+        source,
+        "    nop +0
+l1:
+    acc +1
+    jmp l6
+l3:
+    acc +3
+    jmp l1
+    acc -99
+l6:
+    acc +1
+    jmp l3
+    acc +6"
+    );
+    assert_eq!(assemble(&source), Ok(program));
+
+    let program = vec![
+        Instruction::Jmp(3),
+        Instruction::Jmp(-5),
+        Instruction::Acc(1),
+    ];
+    assert_eq!(
+        disassemble(&program),
+        "    jmp end\n    jmp -5\n    acc +1\nend:"
+    );
+}
+
+#[test]
+fn test_encoding() {
+    let program = super::example();
+    let bytes = encode(&program);
+    assert_eq!(bytes.len(), MAGIC.len() + 1 + 9 + 1);
+    assert_eq!(decode(&bytes), Ok(program));
+
+    let large = vec![Instruction::Acc(isize::MIN), Instruction::Nop(isize::MAX)];
+    assert_eq!(decode(&encode(&large)), Ok(large));
+
+    assert_eq!(decode(b"HHC"), Err(DecodeError::BadMagic));
+    assert_eq!(decode(b"HHC\x01\x02\x00"), Err(DecodeError::Truncated));
+    assert_eq!(
+        decode(b"HHC\x01\x01\x03"),
+        Err(DecodeError::UnknownOpcode {
+            offset: 5,
+            opcode: 3
+        })
+    );
+}
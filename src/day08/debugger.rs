@@ -0,0 +1,363 @@
+//! An interactive debugger for day 8 programs.
+
+use super::machine::{Machine, Termination};
+use super::{Instruction, OPCODES};
+use std::io::{self, BufRead, Write};
+
+static HELP: &str = "Commands:
+    step [n]              run the next n instructions, 1 by default
+    continue              run until a breakpoint, a watch or the end
+    break <index|opcode>  stop before the instruction at index, or any with opcode
+    delete <index|opcode> remove a breakpoint
+    watch                 toggle stopping whenever the accumulator changes
+    print                 show the program counter, accumulator and next instruction
+    history [n]           show the last n instructions run, 10 by default
+    rewind [n]            undo the last n instructions, 1 by default
+    help                  show this message
+    quit                  leave the debugger";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Breakpoint {
+    Index(usize),
+    Opcode(&'static str),
+}
+
+impl Breakpoint {
+    fn parse(arg: &str) -> Result<Self, String> {
+        if let Some(&opcode) = OPCODES.iter().find(|&&opcode| opcode == arg) {
+            return Ok(Breakpoint::Opcode(opcode));
+        }
+        arg.parse()
+            .map(Breakpoint::Index)
+            .map_err(|_| format!("`{}` is neither an index nor an opcode", arg))
+    }
+
+    fn matches(&self, index: usize, instruction: &Instruction) -> bool {
+        match self {
+            Breakpoint::Index(i) => *i == index,
+            Breakpoint::Opcode(opcode) => *opcode == instruction.opcode(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Step(usize),
+    Continue,
+    Break(Breakpoint),
+    Delete(Breakpoint),
+    Watch,
+    Print,
+    History(usize),
+    Rewind(usize),
+    Help,
+    Quit,
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let count = |default| match words.get(1) {
+            Some(n) => n
+                .parse()
+                .map_err(|_| format!("`{}` is not a valid count", n)),
+            None => Ok(default),
+        };
+        let breakpoint = || match words.get(1) {
+            Some(arg) => Breakpoint::parse(arg),
+            None => Err(String::from("expected an index or an opcode")),
+        };
+
+        match words.first().copied().unwrap_or("") {
+            "s" | "step" => count(1).map(Command::Step),
+            "c" | "continue" => Ok(Command::Continue),
+            "b" | "break" => breakpoint().map(Command::Break),
+            "d" | "delete" => breakpoint().map(Command::Delete),
+            "w" | "watch" => Ok(Command::Watch),
+            "p" | "print" => Ok(Command::Print),
+            "h" | "history" => count(10).map(Command::History),
+            "r" | "rewind" => count(1).map(Command::Rewind),
+            "help" => Ok(Command::Help),
+            "q" | "quit" => Ok(Command::Quit),
+            word => Err(format!("unknown command `{}`, try help", word)),
+        }
+    }
+}
+
+/// An instruction that has been run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Executed {
+    index: usize,
+    acc: isize,
+}
+
+pub struct Debugger<'p> {
+    machine: Machine<'p>,
+    breakpoints: Vec<Breakpoint>,
+    watch: bool,
+    history: Vec<Executed>,
+}
+
+impl<'p> Debugger<'p> {
+    pub fn new(program: &'p [Instruction]) -> Self {
+        Debugger {
+            machine: Machine::new(program),
+            breakpoints: vec![],
+            watch: false,
+            history: vec![],
+        }
+    }
+
+    pub fn machine(&self) -> &Machine<'p> {
+        &self.machine
+    }
+
+    /// Runs `command`, returning what to show. `None` means quitting.
+    pub fn execute(&mut self, command: Command) -> Option<String> {
+        let output = match command {
+            Command::Step(n) => {
+                let mut output = vec![];
+                for _ in 0..n {
+                    match self.step() {
+                        Ok(line) => output.push(line),
+                        Err(termination) => {
+                            output.push(describe(termination));
+                            break;
+                        }
+                    }
+                }
+                output.join("\n")
+            }
+            Command::Continue => self.resume(),
+            Command::Break(breakpoint) => {
+                if !self.breakpoints.contains(&breakpoint) {
+                    self.breakpoints.push(breakpoint);
+                }
+                format!("{} breakpoint(s)", self.breakpoints.len())
+            }
+            Command::Delete(breakpoint) => {
+                self.breakpoints.retain(|b| *b != breakpoint);
+                format!("{} breakpoint(s)", self.breakpoints.len())
+            }
+            Command::Watch => {
+                self.watch = !self.watch;
+                format!("watching the accumulator: {}", self.watch)
+            }
+            Command::Print => self.position(),
+            Command::History(n) => {
+                let start = self.history.len().saturating_sub(n);
+                self.history[start..]
+                    .iter()
+                    .enumerate()
+                    .map(|(i, executed)| {
+                        format!(
+                            "#{:<5} {:>5}: {:<8} acc {}",
+                            start + i + 1,
+                            executed.index,
+                            self.machine.program()[executed.index],
+                            executed.acc
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+            Command::Rewind(n) => {
+                self.rewind(n);
+                self.position()
+            }
+            Command::Help => HELP.to_string(),
+            Command::Quit => return None,
+        };
+        Some(output)
+    }
+
+    /// Runs one instruction, describing it, or tells why there is none.
+    fn step(&mut self) -> Result<String, Termination> {
+        let index = self.machine.pc() as usize;
+        let instruction = self.machine.current();
+        if let Some(termination) = self.machine.step() {
+            return Err(termination);
+        }
+        let acc = self.machine.acc();
+        self.history.push(Executed { index, acc });
+        Ok(format!(
+            "{:>5}: {:<8} acc {}",
+            index,
+            instruction.map(ToString::to_string).unwrap_or_default(),
+            acc
+        ))
+    }
+
+    fn resume(&mut self) -> String {
+        loop {
+            let acc = self.machine.acc();
+            if let Err(termination) = self.step() {
+                return describe(termination);
+            }
+            if self.watch && self.machine.acc() != acc {
+                return format!(
+                    "accumulator {} -> {}\n{}",
+                    acc,
+                    self.machine.acc(),
+                    self.position()
+                );
+            }
+
+            let index = self.machine.pc();
+            if let Some(instruction) = self.machine.current() {
+                let index = index as usize;
+                if self
+                    .breakpoints
+                    .iter()
+                    .any(|b| b.matches(index, instruction))
+                {
+                    return format!("breakpoint\n{}", self.position());
+                }
+                if self.machine.executions(index) > 0 {
+                    return format!(
+                        "instruction {} is about to run a second time\n{}",
+                        index,
+                        self.position()
+                    );
+                }
+            }
+        }
+    }
+
+    /// Runs the program again from the start, up to `n` instructions ago.
+    fn rewind(&mut self, n: usize) {
+        let steps = self.history.len().saturating_sub(n);
+        self.history.truncate(steps);
+        self.machine = Machine::new(self.machine.program());
+        for _ in 0..steps {
+            self.machine.step();
+        }
+    }
+
+    fn position(&self) -> String {
+        match self.machine.current() {
+            Some(instruction) => format!(
+                "pc {}, acc {}, next {}",
+                self.machine.pc(),
+                self.machine.acc(),
+                instruction
+            ),
+            None => format!(
+                "pc {}, acc {}, no instruction left",
+                self.machine.pc(),
+                self.machine.acc()
+            ),
+        }
+    }
+}
+
+fn describe(termination: Termination) -> String {
+    match termination {
+        Termination::Exit { pc, acc } => format!("program exited at pc {}, acc {}", pc, acc),
+        Termination::JumpPastEnd { pc, acc } => {
+            format!("program jumped past its end to pc {}, acc {}", pc, acc)
+        }
+        Termination::JumpBeforeStart { pc, acc } => {
+            format!("program jumped before its start to pc {}, acc {}", pc, acc)
+        }
+        Termination::Halt { pc, acc } => format!("program halted at pc {}, acc {}", pc, acc),
+        Termination::InfiniteLoop { pc, acc } => {
+            format!("program loops at pc {}, acc {}", pc, acc)
+        }
+    }
+}
+
+/// Reads commands from `input` until it ends or `quit`, writing the results to `output`.
+pub fn repl(
+    program: &[Instruction],
+    input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<()> {
+    let mut debugger = Debugger::new(program);
+    writeln!(
+        output,
+        "{} instructions loaded, type help for the commands",
+        program.len()
+    )?;
+
+    let mut lines = input.lines();
+    loop {
+        write!(output, "(day08) ")?;
+        output.flush()?;
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => return Ok(()),
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let shown = match Command::parse(&line) {
+            Ok(command) => match debugger.execute(command) {
+                Some(shown) => shown,
+                None => return Ok(()),
+            },
+            Err(e) => e,
+        };
+        if !shown.is_empty() {
+            writeln!(output, "{}", shown)?;
+        }
+    }
+}
+
+#[test]
+fn test_commands() {
+    assert_eq!(Command::parse("step 3"), Ok(Command::Step(3)));
+    assert_eq!(
+        Command::parse("b jmp"),
+        Ok(Command::Break(Breakpoint::Opcode("jmp")))
+    );
+    assert_eq!(
+        Command::parse("break 4"),
+        Ok(Command::Break(Breakpoint::Index(4)))
+    );
+    assert!(Command::parse("break").is_err());
+    assert!(Command::parse("rewind x").is_err());
+    assert!(Command::parse("jump").is_err());
+}
+
+#[test]
+fn test_debugger() {
+    let program = super::example();
+    let mut debugger = Debugger::new(&program);
+
+    debugger.execute(Command::Break(Breakpoint::Opcode("jmp")));
+    debugger.execute(Command::Continue);
+    assert_eq!((debugger.machine().pc(), debugger.machine().acc()), (2, 1));
+
+    debugger.execute(Command::Delete(Breakpoint::Opcode("jmp")));
+    debugger.execute(Command::Watch);
+    debugger.execute(Command::Continue);
+    assert_eq!((debugger.machine().pc(), debugger.machine().acc()), (7, 2));
+
+    debugger.execute(Command::Rewind(2));
+    assert_eq!((debugger.machine().pc(), debugger.machine().acc()), (2, 1));
+    assert_eq!(
+        debugger.execute(Command::History(1)),
+        Some(String::from("#2         1: acc +1   acc 1"))
+    );
+
+    debugger.execute(Command::Watch);
+    let stopped = debugger.execute(Command::Continue).unwrap();
+    assert!(stopped.starts_with("instruction 1 is about to run a second time"));
+    assert_eq!(debugger.execute(Command::Quit), None);
+}
+
+#[test]
+fn test_repl() {
+    let program = super::example();
+    let mut output = vec![];
+    repl(
+        &program,
+        "step 2\nprint\nquit\nstep\n".as_bytes(),
+        &mut output,
+    )
+    .unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.ends_with("(day08) pc 2, acc 1, next jmp +4\n(day08) "));
+}
@@ -0,0 +1,296 @@
+//! The handheld console running day 8 programs, or programs of any
+//! instruction set implementing [`Execute`].
+
+use super::Instruction;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// What the machine does after an instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    /// Go on with the following instruction.
+    Next,
+    /// Move the program counter by the offset.
+    Jump(isize),
+    /// Stop the machine.
+    Halt,
+}
+
+/// An instruction the machine can run.
+pub trait Execute {
+    fn execute(&self, state: &mut State) -> Flow;
+}
+
+impl Execute for Instruction {
+    fn execute(&self, state: &mut State) -> Flow {
+        match *self {
+            Instruction::Acc(val) => {
+                state.acc += val;
+                Flow::Next
+            }
+            Instruction::Jmp(offset) => Flow::Jump(offset),
+            Instruction::Nop(_) => Flow::Next,
+        }
+    }
+}
+
+/// Everything instructions can read and change.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct State {
+    /// Index of the next instruction.
+    pub pc: isize,
+    pub acc: isize,
+    /// Registers other than the accumulator, 0 until written.
+    pub registers: HashMap<char, isize>,
+    /// Values emitted by the program.
+    pub output: Vec<isize>,
+}
+
+impl State {
+    pub fn register(&self, register: char) -> isize {
+        self.registers.get(&register).copied().unwrap_or(0)
+    }
+
+    pub fn register_mut(&mut self, register: char) -> &mut isize {
+        self.registers.entry(register).or_insert(0)
+    }
+}
+
+/// Why the machine stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Termination {
+    /// The program counter reached right after the last instruction.
+    Exit { pc: isize, acc: isize },
+    /// The program counter went further than right after the last instruction.
+    JumpPastEnd { pc: isize, acc: isize },
+    /// The program counter went negative.
+    JumpBeforeStart { pc: isize, acc: isize },
+    /// An instruction stopped the machine.
+    Halt { pc: isize, acc: isize },
+    /// The instruction at `pc` was about to run a second time.
+    InfiniteLoop { pc: isize, acc: isize },
+}
+
+impl Termination {
+    pub fn pc(&self) -> isize {
+        match *self {
+            Termination::Exit { pc, .. }
+            | Termination::JumpPastEnd { pc, .. }
+            | Termination::JumpBeforeStart { pc, .. }
+            | Termination::Halt { pc, .. }
+            | Termination::InfiniteLoop { pc, .. } => pc,
+        }
+    }
+
+    pub fn acc(&self) -> isize {
+        match *self {
+            Termination::Exit { acc, .. }
+            | Termination::JumpPastEnd { acc, .. }
+            | Termination::JumpBeforeStart { acc, .. }
+            | Termination::Halt { acc, .. }
+            | Termination::InfiniteLoop { acc, .. } => acc,
+        }
+    }
+}
+
+pub struct Machine<'p, I = Instruction> {
+    program: &'p [I],
+    state: State,
+    halted: bool,
+    executions: Vec<usize>,
+}
+
+impl<'p, I: Execute> Machine<'p, I> {
+    pub fn new(program: &'p [I]) -> Self {
+        Machine {
+            program,
+            state: State::default(),
+            halted: false,
+            executions: vec![0; program.len()],
+        }
+    }
+
+    pub fn program(&self) -> &'p [I] {
+        self.program
+    }
+
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    pub fn pc(&self) -> isize {
+        self.state.pc
+    }
+
+    pub fn acc(&self) -> isize {
+        self.state.acc
+    }
+
+    /// How many times the instruction at `index` has run.
+    pub fn executions(&self, index: usize) -> usize {
+        self.executions.get(index).copied().unwrap_or(0)
+    }
+
+    /// Index of the next instruction, if there is one.
+    fn next_index(&self) -> Option<usize> {
+        if self.state.pc >= 0 && (self.state.pc as usize) < self.program.len() {
+            Some(self.state.pc as usize)
+        } else {
+            None
+        }
+    }
+
+    /// The next instruction, unless the machine has stopped.
+    pub fn current(&self) -> Option<&'p I> {
+        match self.halted {
+            true => None,
+            false => self.next_index().map(|i| &self.program[i]),
+        }
+    }
+
+    /// Why the machine can't run any further, if it can't.
+    pub fn termination(&self) -> Option<Termination> {
+        let (pc, acc) = (self.state.pc, self.state.acc);
+        let len = self.program.len() as isize;
+        if self.halted {
+            Some(Termination::Halt { pc, acc })
+        } else if pc < 0 {
+            Some(Termination::JumpBeforeStart { pc, acc })
+        } else if pc == len {
+            Some(Termination::Exit { pc, acc })
+        } else if pc > len {
+            Some(Termination::JumpPastEnd { pc, acc })
+        } else {
+            None
+        }
+    }
+
+    /// Runs the next instruction, or tells why there is none.
+    pub fn step(&mut self) -> Option<Termination> {
+        if let Some(termination) = self.termination() {
+            return Some(termination);
+        }
+        let index = self.state.pc as usize;
+        self.executions[index] += 1;
+
+        match self.program[index].execute(&mut self.state) {
+            Flow::Next => self.state.pc += 1,
+            Flow::Jump(offset) => self.state.pc += offset,
+            Flow::Halt => self.halted = true,
+        }
+        None
+    }
+
+    /// Runs until the program stops or an instruction is about to run again.
+    pub fn run(&mut self) -> Termination {
+        self.run_with(|_, _| {})
+    }
+
+    /// Like [`run`](Self::run), calling `observe` with the index of every
+    /// instruction run and the state it leaves.
+    pub fn run_with(&mut self, mut observe: impl FnMut(usize, &State)) -> Termination {
+        loop {
+            if let Some(i) = self.next_index() {
+                if !self.halted && self.executions[i] > 0 {
+                    return Termination::InfiniteLoop {
+                        pc: self.state.pc,
+                        acc: self.state.acc,
+                    };
+                }
+            }
+            let index = self.state.pc as usize;
+            if let Some(termination) = self.step() {
+                return termination;
+            }
+            observe(index, &self.state);
+        }
+    }
+}
+
+#[cfg(test)]
+enum Extended {
+    Set(char, isize),
+    Add(char, char),
+    Jnz(char, isize),
+    Out(char),
+    Hlt,
+}
+
+#[cfg(test)]
+impl Execute for Extended {
+    fn execute(&self, state: &mut State) -> Flow {
+        match *self {
+            Extended::Set(r, val) => *state.register_mut(r) = val,
+            Extended::Add(r, s) => *state.register_mut(r) += state.register(s),
+            Extended::Jnz(r, offset) if state.register(r) != 0 => return Flow::Jump(offset),
+            Extended::Jnz(..) => {}
+            Extended::Out(r) => state.output.push(state.register(r)),
+            Extended::Hlt => return Flow::Halt,
+        }
+        Flow::Next
+    }
+}
+
+#[test]
+fn test_instructions() {
+    let program = [
+        Instruction::Nop(0),
+        Instruction::Acc(1),
+        Instruction::Jmp(4),
+        Instruction::Acc(3),
+        Instruction::Jmp(-3),
+        Instruction::Acc(-99),
+        Instruction::Acc(1),
+        Instruction::Jmp(-4),
+        Instruction::Acc(6),
+    ];
+    let mut machine = Machine::new(&program);
+    assert_eq!(machine.step(), None);
+    assert_eq!((machine.pc(), machine.acc()), (1, 0));
+    assert_eq!(machine.run(), Termination::InfiniteLoop { pc: 1, acc: 5 });
+    assert_eq!(machine.executions(1), 1);
+    assert_eq!(machine.executions(5), 0);
+}
+
+#[test]
+fn test_extended_instructions() {
+    // Counts down from 3, printing the running total.
+    let program = [
+        Extended::Set('n', 3),
+        Extended::Set('m', -1),
+        Extended::Add('t', 'n'),
+        Extended::Out('t'),
+        Extended::Add('n', 'm'),
+        Extended::Jnz('n', -3),
+        Extended::Hlt,
+        Extended::Out('n'),
+    ];
+    let mut machine = Machine::new(&program);
+    assert_eq!(machine.run(), Termination::InfiniteLoop { pc: 2, acc: 0 });
+
+    let mut machine = Machine::new(&program);
+    while machine.step().is_none() {}
+    assert_eq!(
+        machine.termination(),
+        Some(Termination::Halt { pc: 6, acc: 0 })
+    );
+    assert_eq!(machine.state().output, vec![3, 5, 6]);
+}
+
+#[test]
+fn test_terminations() {
+    use Instruction::*;
+
+    let run = |program: &[Instruction]| Machine::new(program).run();
+    assert_eq!(run(&[Acc(2), Nop(5)]), Termination::Exit { pc: 2, acc: 2 });
+    assert_eq!(
+        run(&[Acc(2), Jmp(5)]),
+        Termination::JumpPastEnd { pc: 6, acc: 2 }
+    );
+    assert_eq!(
+        run(&[Acc(2), Jmp(-3)]),
+        Termination::JumpBeforeStart { pc: -2, acc: 2 }
+    );
+    assert_eq!(run(&[]), Termination::Exit { pc: 0, acc: 0 });
+}
@@ -0,0 +1,168 @@
+//! Finding the `jmp`/`nop` swap that makes a looping program terminate, in
+//! linear time: the instructions from which the program ends, and what they
+//! add to the accumulator until then, are found going backwards from the end,
+//! then the path the program follows is walked once looking for an
+//! instruction whose swap leads into them.
+
+use super::Instruction;
+use std::fmt::{self, Display};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repair {
+    pub index: usize,
+    /// What the instruction at `index` becomes.
+    pub instruction: Instruction,
+    /// Accumulator when the repaired program terminates.
+    pub acc: isize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepairError {
+    AlreadyTerminates { acc: isize },
+    NoRepair,
+    Ambiguous(Vec<Repair>),
+}
+
+impl Display for RepairError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RepairError::AlreadyTerminates { acc } => {
+                write!(f, "the program terminates without any swap, acc {}", acc)
+            }
+            RepairError::NoRepair => {
+                write!(f, "no single jmp/nop swap makes the program terminate")
+            }
+            RepairError::Ambiguous(repairs) => {
+                let indexes: Vec<String> = repairs.iter().map(|r| r.index.to_string()).collect();
+                write!(
+                    f,
+                    "{} swaps make the program terminate, at {}",
+                    repairs.len(),
+                    indexes.join(", ")
+                )
+            }
+        }
+    }
+}
+
+/// Index of the instruction run after `instruction`, at `index`. The end of
+/// the program is `len`, anything out of `0..=len` is `None`.
+pub(super) fn successor(index: usize, instruction: &Instruction, len: usize) -> Option<usize> {
+    let next = match instruction {
+        Instruction::Jmp(offset) => index as isize + offset,
+        _ => index as isize + 1,
+    };
+    if next >= 0 && next as usize <= len {
+        Some(next as usize)
+    } else {
+        None
+    }
+}
+
+/// For each instruction, what the accumulator gains from it until the
+/// program ends, running right after its last instruction, `None` if the
+/// program doesn't end from there. The last element is the end itself.
+fn reaching_end(program: &[Instruction]) -> Vec<Option<isize>> {
+    let len = program.len();
+    let mut predecessors = vec![vec![]; len + 1];
+    for (i, instruction) in program.iter().enumerate() {
+        if let Some(next) = successor(i, instruction, len) {
+            predecessors[next].push(i);
+        }
+    }
+
+    // Every instruction has one successor, so each gain is set once.
+    let mut gains = vec![None; len + 1];
+    let mut stack = vec![len];
+    gains[len] = Some(0);
+    while let Some(i) = stack.pop() {
+        let gain = gains[i].unwrap_or(0);
+        for &p in &predecessors[i] {
+            if gains[p].is_none() {
+                gains[p] = Some(gain + increment(&program[p]));
+                stack.push(p);
+            }
+        }
+    }
+    gains
+}
+
+fn increment(instruction: &Instruction) -> isize {
+    match instruction {
+        Instruction::Acc(val) => *val,
+        _ => 0,
+    }
+}
+
+/// Every single swap along the path of `program` making it terminate.
+fn repairs(program: &[Instruction], gains: &[Option<isize>]) -> Vec<Repair> {
+    let len = program.len();
+    let mut visited = vec![false; len];
+    let mut repairs = vec![];
+    let mut pc = Some(0);
+    let mut acc = 0;
+
+    while let Some(i) = pc.filter(|&i| i < len && !visited[i]) {
+        visited[i] = true;
+        if let Some(flipped) = program[i].flipped() {
+            // The swapped instruction is a jmp or a nop, adding nothing.
+            if let Some(gain) = successor(i, &flipped, len).and_then(|next| gains[next]) {
+                repairs.push(Repair {
+                    index: i,
+                    instruction: flipped,
+                    acc: acc + gain,
+                });
+            }
+        }
+        acc += increment(&program[i]);
+        pc = successor(i, &program[i], len);
+    }
+    repairs
+}
+
+pub fn repair(program: &[Instruction]) -> Result<Repair, RepairError> {
+    let gains = reaching_end(program);
+    if let Some(acc) = gains[0] {
+        return Err(RepairError::AlreadyTerminates { acc });
+    }
+
+    let mut repairs = repairs(program, &gains);
+    match repairs.len() {
+        0 => Err(RepairError::NoRepair),
+        1 => Ok(repairs.remove(0)),
+        _ => Err(RepairError::Ambiguous(repairs)),
+    }
+}
+
+#[test]
+fn test_repair() {
+    assert_eq!(
+        repair(&super::example()),
+        Ok(Repair {
+            index: 7,
+            instruction: Instruction::Nop(-4),
+            acc: 8
+        })
+    );
+}
+
+#[test]
+fn test_repair_errors() {
+    use Instruction::*;
+
+    assert_eq!(
+        repair(&[Acc(2), Nop(5)]),
+        Err(RepairError::AlreadyTerminates { acc: 2 })
+    );
+    assert_eq!(
+        repair(&[Nop(0), Jmp(-1), Jmp(-2)]),
+        Err(RepairError::NoRepair)
+    );
+    // Either the nop jumps over the loop or the loop becomes a nop.
+    let ambiguous = repair(&[Nop(2), Jmp(0), Acc(1)]);
+    assert!(matches!(ambiguous, Err(RepairError::Ambiguous(ref r)) if r.len() == 2));
+    assert_eq!(
+        ambiguous.unwrap_err().to_string(),
+        "2 swaps make the program terminate, at 0, 1"
+    );
+}
@@ -0,0 +1,196 @@
+//! Execution traces of day 8 programs, and their control-flow graph in Graphviz DOT.
+
+use super::machine::{Machine, Termination};
+use super::Instruction;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Step {
+    /// Index of the instruction run.
+    pub pc: usize,
+    pub instruction: String,
+    /// Accumulator after the instruction.
+    pub acc: isize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Trace {
+    pub steps: Vec<Step>,
+    pub termination: Termination,
+}
+
+impl Trace {
+    pub fn run(program: &[Instruction]) -> Self {
+        let mut steps = vec![];
+        let termination = Machine::new(program).run_with(|pc, state| {
+            steps.push(Step {
+                pc,
+                instruction: program[pc].to_string(),
+                acc: state.acc,
+            })
+        });
+        Trace { steps, termination }
+    }
+
+    /// The steps repeated forever, empty unless the program loops.
+    pub fn cycle(&self) -> &[Step] {
+        match self.termination {
+            Termination::InfiniteLoop { pc, .. } => {
+                let start = self
+                    .steps
+                    .iter()
+                    .position(|step| step.pc as isize == pc)
+                    .unwrap_or(self.steps.len());
+                &self.steps[start..]
+            }
+            _ => &[],
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("traces serialize to JSON")
+    }
+}
+
+fn node(program: &[Instruction], target: isize) -> String {
+    if target >= 0 && (target as usize) < program.len() {
+        format!("i{}", target)
+    } else if target == program.len() as isize {
+        String::from("exit")
+    } else {
+        format!("out{}", target).replace('-', "_")
+    }
+}
+
+/// The control-flow graph of `program`: one node per instruction, an edge to
+/// the following instruction or the jump target. Instructions and edges run
+/// by `trace` are bold, the ones of its loop red. The jump a `nop` would do if
+/// it were a `jmp` is dotted.
+pub fn dot(program: &[Instruction], trace: &Trace) -> String {
+    let executed: HashSet<usize> = trace.steps.iter().map(|s| s.pc).collect();
+    let taken: HashSet<(usize, isize)> = trace
+        .steps
+        .windows(2)
+        .map(|w| (w[0].pc, w[1].pc as isize))
+        .chain(
+            trace
+                .steps
+                .last()
+                .map(|last| (last.pc, trace.termination.pc())),
+        )
+        .collect();
+    let cycle: HashSet<usize> = trace.cycle().iter().map(|s| s.pc).collect();
+
+    let mut dot = String::from("digraph program {\n    node [shape=box, fontname=monospace];\n");
+    let mut outside = HashSet::new();
+
+    for (i, instruction) in program.iter().enumerate() {
+        let style = if cycle.contains(&i) {
+            ", style=\"filled,bold\", fillcolor=mistyrose, color=red"
+        } else if executed.contains(&i) {
+            ", style=\"filled,bold\", fillcolor=lightblue"
+        } else {
+            ""
+        };
+        writeln!(
+            dot,
+            "    i{} [label=\"{}: {}\"{}];",
+            i, i, instruction, style
+        )
+        .unwrap();
+
+        let jump = i as isize + instruction.argument();
+        let mut edges = vec![];
+        match instruction {
+            Instruction::Jmp(_) => edges.push((jump, "")),
+            Instruction::Acc(_) => edges.push((i as isize + 1, "")),
+            Instruction::Nop(_) => {
+                edges.push((i as isize + 1, ""));
+                edges.push((jump, "style=dotted, color=gray"));
+            }
+        }
+
+        for (target, base) in edges {
+            let target_node = node(program, target);
+            if !target_node.starts_with('i') {
+                outside.insert((target_node.clone(), target));
+            }
+            let style = if !base.is_empty() {
+                base.to_string()
+            } else if cycle.contains(&i) && taken.contains(&(i, target)) {
+                String::from("color=red, penwidth=2")
+            } else if taken.contains(&(i, target)) {
+                String::from("penwidth=2")
+            } else {
+                String::new()
+            };
+            match style.as_str() {
+                "" => writeln!(dot, "    i{} -> {};", i, target_node),
+                _ => writeln!(dot, "    i{} -> {} [{}];", i, target_node, style),
+            }
+            .unwrap();
+        }
+    }
+
+    let mut outside: Vec<_> = outside.into_iter().collect();
+    outside.sort();
+    for (name, target) in outside {
+        let label = match name.as_str() {
+            "exit" => String::from("exit"),
+            _ if target < 0 => format!("before start, pc {}", target),
+            _ => format!("past end, pc {}", target),
+        };
+        writeln!(
+            dot,
+            "    {} [label=\"{}\", shape=doublecircle];",
+            name, label
+        )
+        .unwrap();
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+#[test]
+fn test_trace() {
+    let trace = Trace::run(&super::example());
+    let pcs: Vec<usize> = trace.steps.iter().map(|s| s.pc).collect();
+    assert_eq!(pcs, vec![0, 1, 2, 6, 7, 3, 4]);
+    assert_eq!(
+        trace.termination,
+        Termination::InfiniteLoop { pc: 1, acc: 5 }
+    );
+    assert_eq!(trace.cycle().len(), 6);
+
+    let json: serde_json::Value = serde_json::from_str(&trace.to_json()).unwrap();
+    assert_eq!(json["steps"][2]["instruction"], "jmp +4");
+    assert_eq!(json["steps"][6]["acc"], 5);
+    assert_eq!(json["termination"]["kind"], "infinite_loop");
+}
+
+#[test]
+fn test_dot() {
+    let program = super::example();
+    let dot = dot(&program, &Trace::run(&program));
+    assert!(dot.starts_with("digraph program {"));
+    assert!(dot.contains("i0 [label=\"0: nop +0\", style=\"filled,bold\", fillcolor=lightblue];"));
+    assert!(dot.contains(
+        "i1 [label=\"1: acc +1\", style=\"filled,bold\", fillcolor=mistyrose, color=red];"
+    ));
+    assert!(dot.contains("i5 [label=\"5: acc -99\"];"));
+    assert!(dot.contains("i4 -> i1 [color=red, penwidth=2];"));
+    assert!(dot.contains("i0 -> i1 [penwidth=2];"));
+    assert!(dot.contains("i0 -> i0 [style=dotted, color=gray];"));
+    assert!(dot.contains("i8 -> exit;"));
+    assert!(dot.contains("exit [label=\"exit\", shape=doublecircle];"));
+}
+
+#[test]
+fn test_dot_out_of_bounds() {
+    let program = [Instruction::Jmp(-2), Instruction::Jmp(3)];
+    let dot = dot(&program, &Trace::run(&program));
+    assert!(dot.contains("out_2 [label=\"before start, pc -2\", shape=doublecircle];"));
+    assert!(dot.contains("out4 [label=\"past end, pc 4\", shape=doublecircle];"));
+}
@@ -1,10 +1,51 @@
+use crate::parse::{number, parse_lines};
+use crate::{Error, Part, Result, Solution};
 use itertools::Itertools;
 
-pub fn p1(input: &str) -> usize {
-    find_invalid(&parse(input), 25).unwrap()
+pub struct Day09;
+
+impl Solution for Day09 {
+    const DAY: u8 = 9;
+    type Input = Vec<usize>;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Vec<usize>> {
+        parse_lines(Self::DAY, input, |l| number(l, l))
+    }
+
+    fn p1(data: &Vec<usize>) -> Result<usize> {
+        find_invalid(data, 25).ok_or_else(|| no_invalid_number(Part::One))
+    }
+
+    fn p2(data: &Vec<usize>) -> Result<usize> {
+        let invalid = find_invalid(data, 25).ok_or_else(|| no_invalid_number(Part::Two))?;
+        (2..data.len())
+            .find_map(|set_len| {
+                data.windows(set_len)
+                    .find_map(|set| match set.iter().sum::<usize>() == invalid {
+                        true => Some(set.iter().max().unwrap() + set.iter().min().unwrap()),
+                        false => None,
+                    })
+            })
+            .ok_or_else(|| {
+                Error::no_answer(
+                    Self::DAY,
+                    Part::Two,
+                    format!("no contiguous set sums to {}", invalid),
+                )
+            })
+    }
 }
 
-fn find_invalid(data: &Vec<usize>, preamble_len: usize) -> Option<usize> {
+fn no_invalid_number(part: Part) -> Error {
+    Error::no_answer(
+        Day09::DAY,
+        part,
+        "every number is a sum of two previous ones",
+    )
+}
+
+fn find_invalid(data: &[usize], preamble_len: usize) -> Option<usize> {
     data.windows(preamble_len + 1).find_map(|w| {
         match w[0..=preamble_len]
             .iter()
@@ -17,24 +58,6 @@ fn find_invalid(data: &Vec<usize>, preamble_len: usize) -> Option<usize> {
     })
 }
 
-pub fn p2(input: &str) -> usize {
-    let data = parse(input);
-    let invalid = find_invalid(&data, 25).unwrap();
-    (2..data.len())
-        .find_map(|set_len| {
-            data.windows(set_len)
-                .find_map(|set| match set.iter().sum::<usize>() == invalid {
-                    true => Some(set.iter().max().unwrap() + set.iter().min().unwrap()),
-                    false => None,
-                })
-        })
-        .unwrap()
-}
-
-fn parse(input: &str) -> Vec<usize> {
-    input.lines().map(|l| l.parse::<usize>().unwrap()).collect()
-}
-
 #[test]
 fn test_resolve() {
     assert_eq!(
@@ -52,10 +75,16 @@ fn test_resolve() {
 
 #[test]
 fn test_p1() {
-    assert_eq!(p1(include_str!("../inputs/day09.txt")), 1309761972);
+    assert_eq!(
+        Day09::solve_p1(&crate::input::load(9).unwrap()),
+        Ok(1309761972)
+    );
 }
 
 #[test]
 fn test_p2() {
-    assert_eq!(p2(include_str!("../inputs/day09.txt")), 177989832);
+    assert_eq!(
+        Day09::solve_p2(&crate::input::load(9).unwrap()),
+        Ok(177989832)
+    );
 }
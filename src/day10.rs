@@ -1,44 +1,57 @@
-use std::collections::HashMap;
-use std::iter;
+use crate::error::Malformed;
+use crate::parse::{number, parse_lines};
+use crate::{Result, Solution};
+use std::collections::{HashMap, HashSet};
 
 type Adaptors = Vec<usize>;
 
-pub fn p1(input: &str) -> usize {
-    let adaptors = parse(input);
-    let (a, b): (Vec<usize>, Vec<usize>) = adaptors
-        .windows(2)
-        .map(|w| w[1] - w[0])
-        .partition(|&n| n == 1);
-    a.len() * b.len()
-}
+pub struct Day10;
 
-pub fn p2(input: &str) -> usize {
-    let adaptors = parse(input);
-    let mut options: HashMap<usize, usize> = HashMap::new();
-    options.insert(0, 1);
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+    type Input = Adaptors;
+    type Answer = usize;
 
-    for a in &adaptors[1..] {
-        let diff_range = match a {
-            1 | 2 => (1..=*a),
-            _ => (1..=3),
-        };
-        options.insert(
-            *a,
-            diff_range.filter_map(|diff| options.get(&(a - diff))).sum(),
-        );
+    fn parse(input: &str) -> Result<Adaptors> {
+        let mut seen = HashSet::new();
+        let mut parsed = parse_lines(Self::DAY, input, |l| match number(l, l)? {
+            0 => Err(Malformed::new(1, "expected a joltage above the outlet's 0")),
+            n if !seen.insert(n) => {
+                Err(Malformed::new(1, format!("adaptor {} is listed twice", n)))
+            }
+            n => Ok(n),
+        })?;
+        parsed.push(0);
+        parsed.sort();
+        parsed.push(parsed[parsed.len() - 1] + 3);
+        Ok(parsed)
     }
 
-    *options.get(&(adaptors[adaptors.len() - 1])).unwrap()
-}
+    fn p1(adaptors: &Adaptors) -> Result<usize> {
+        let (a, b): (Vec<usize>, Vec<usize>) = adaptors
+            .windows(2)
+            .map(|w| w[1] - w[0])
+            .partition(|&n| n == 1);
+        Ok(a.len() * b.len())
+    }
+
+    fn p2(adaptors: &Adaptors) -> Result<usize> {
+        let mut options: HashMap<usize, usize> = HashMap::new();
+        options.insert(0, 1);
+
+        for a in &adaptors[1..] {
+            let diff_range = match a {
+                1 | 2 => 1..=*a,
+                _ => 1..=3,
+            };
+            options.insert(
+                *a,
+                diff_range.filter_map(|diff| options.get(&(a - diff))).sum(),
+            );
+        }
 
-fn parse(input: &str) -> Adaptors {
-    let mut parsed = iter::once("0")
-        .chain(input.lines())
-        .map(|l| l.parse::<usize>().unwrap())
-        .collect::<Vec<usize>>();
-    parsed.sort();
-    parsed.push(parsed[parsed.len() - 1] + 3);
-    parsed
+        Ok(options[&adaptors[adaptors.len() - 1]])
+    }
 }
 
 #[test]
@@ -54,15 +67,25 @@ fn test_dummy_data() {
 6
 12
 4";
-    assert_eq!(p1(data), 7 * 5);
+    assert_eq!(Day10::solve_p1(data), Ok(7 * 5));
+}
+
+#[test]
+fn test_invalid_adaptors() {
+    let error = |input| Day10::parse(input).err().unwrap().to_string();
+    assert!(error("0\n3").contains("line 1, column 1: expected a joltage above the outlet's 0"));
+    assert!(error("1\n3\n1").contains("line 3, column 1: adaptor 1 is listed twice"));
 }
 
 #[test]
 fn test_p1() {
-    assert_eq!(p1(include_str!("../inputs/day10.txt")), 2046);
+    assert_eq!(Day10::solve_p1(&crate::input::load(10).unwrap()), Ok(2046));
 }
 
 #[test]
 fn test_p2() {
-    assert_eq!(p2(include_str!("../inputs/day10.txt")), 1157018619904);
+    assert_eq!(
+        Day10::solve_p2(&crate::input::load(10).unwrap()),
+        Ok(1157018619904)
+    );
 }
@@ -1,203 +1,199 @@
-#[derive(Debug, Eq, PartialEq, Clone)]
-enum SeatType {
+use crate::automaton::{Automaton, Rule};
+use crate::grid::{Grid, Point, DIRECTIONS};
+use crate::visualise::Rgb;
+use crate::{Error, Part, Result, Solution};
+use std::fmt::{self, Display};
+
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub enum SeatType {
     Empty,
     Occupied,
     Floor,
 }
 
 impl SeatType {
-    fn from_char(c: char) -> Self {
+    fn from_char(c: char) -> Option<Self> {
         match c {
-            '#' => SeatType::Occupied,
-            'L' => SeatType::Empty,
-            '.' => SeatType::Floor,
-            _ => unreachable!(),
+            '#' => Some(SeatType::Occupied),
+            'L' => Some(SeatType::Empty),
+            '.' => Some(SeatType::Floor),
+            _ => None,
         }
     }
-}
 
-type SeatsMap = Vec<Vec<SeatType>>;
-type Coordinates = (isize, isize);
-
-pub fn p1(input: &str) -> usize {
-    let mut seats_map = parse(input);
-    loop {
-        let (new_seats_map, changes_count) = move_people(seats_map, apply_rules_1);
-        if changes_count == 0 {
-            return count_occupied_seats(&new_seats_map);
+    fn to_char(&self) -> char {
+        match self {
+            SeatType::Occupied => '#',
+            SeatType::Empty => 'L',
+            SeatType::Floor => '.',
         }
-        seats_map = new_seats_map;
     }
 }
 
-pub fn p2(input: &str) -> usize {
-    let mut seats_map = parse(input);
-    loop {
-        let (new_seats_map, changes_count) = move_people(seats_map, apply_rules_2);
-        if changes_count == 0 {
-            return count_occupied_seats(&new_seats_map);
-        }
-        seats_map = new_seats_map;
+impl Display for SeatType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_char())
     }
 }
 
-fn parse(input: &str) -> SeatsMap {
-    input
-        .lines()
-        .map(|line| line.chars().map(SeatType::from_char).collect())
-        .collect()
+type SeatsMap = Grid<SeatType>;
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+    type Input = SeatsMap;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<SeatsMap> {
+        Grid::parse(Self::DAY, input, |c| {
+            SeatType::from_char(c).ok_or_else(|| format!("unknown seat `{}`", c))
+        })
+    }
+
+    fn p1(seats_map: &SeatsMap) -> Result<usize> {
+        settle(seats_map, Adjacent { tolerance: 4 }, Part::One)
+    }
+
+    fn p2(seats_map: &SeatsMap) -> Result<usize> {
+        settle(seats_map, Visible { tolerance: 5 }, Part::Two)
+    }
 }
 
 fn count_occupied_seats(seats_map: &SeatsMap) -> usize {
     seats_map
         .iter()
-        .flatten()
-        .filter(|s| matches!(s, SeatType::Occupied))
+        .filter(|(_, s)| matches!(s, SeatType::Occupied))
         .count()
 }
 
-fn move_people<F>(seats_map: SeatsMap, apply_rules: F) -> (SeatsMap, usize)
-where
-    F: Fn(&SeatsMap, &SeatType, Coordinates) -> Option<SeatType>,
-{
-    let mut count_changes = 0;
-    let new = seats_map
-        .iter()
-        .enumerate()
-        .map(|(i, seats_line)| {
-            seats_line
-                .iter()
-                .enumerate()
-                .map(
-                    |(j, s)| match apply_rules(&seats_map, s, (j as isize, i as isize)) {
-                        Some(new) => {
-                            count_changes += 1;
-                            new
-                        }
-                        _ => s.clone(),
-                    },
-                )
-                .collect()
-        })
-        .collect();
+/// How the seating evolves until a layout comes back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settling {
+    /// Generations before the first layout of the cycle.
+    pub pre_period: usize,
+    /// Generations before the layout comes back, 1 when people stop moving.
+    pub period: usize,
+    /// Occupied seats in each layout of the cycle.
+    pub occupied: Vec<usize>,
+}
 
-    (new, count_changes)
+pub fn simulate(seats_map: &SeatsMap, rule: impl Rule<Cell = SeatType>) -> Settling {
+    let mut automaton = Automaton::new(seats_map.clone(), rule);
+    let (cycle, mut occupied) = automaton.run_until_repeat_observing(count_occupied_seats);
+    Settling {
+        pre_period: cycle.start,
+        period: cycle.period,
+        occupied: occupied.split_off(cycle.start),
+    }
 }
 
-fn apply_rules_1(
-    seats_map: &SeatsMap,
-    seat: &SeatType,
-    coordinates: Coordinates,
-) -> Option<SeatType> {
+/// Every layout from the first one until one comes back.
+pub fn generations(seats_map: &SeatsMap, rule: impl Rule<Cell = SeatType>) -> Vec<SeatsMap> {
+    let mut automaton = Automaton::new(seats_map.clone(), rule);
+    automaton.run_until_repeat_observing(Grid::clone).1
+}
+
+pub fn colour(seat: &SeatType) -> Rgb {
     match seat {
-        SeatType::Occupied => {
-            if iter_adjacents(seats_map, coordinates)
-                .filter(|adja| matches!(adja, SeatType::Occupied))
-                .count()
-                >= 4
-            {
-                Some(SeatType::Empty)
-            } else {
-                None
-            }
-        }
-        SeatType::Empty => {
-            if iter_adjacents(seats_map, coordinates)
-                .filter(|adja| matches!(adja, SeatType::Occupied))
-                .count()
-                == 0
-            {
-                Some(SeatType::Occupied)
-            } else {
-                None
-            }
-        }
-        SeatType::Floor => None,
+        SeatType::Empty => [46, 160, 67],
+        SeatType::Occupied => [218, 54, 51],
+        SeatType::Floor => [48, 54, 61],
     }
 }
 
-fn apply_rules_2(
-    seats_map: &SeatsMap,
+pub fn style(seat: &SeatType) -> (char, Rgb) {
+    (seat.to_char(), colour(seat))
+}
+
+/// Occupied seats once people stop moving.
+fn settle(seats_map: &SeatsMap, rule: impl Rule<Cell = SeatType>, part: Part) -> Result<usize> {
+    let settling = simulate(seats_map, rule);
+    match settling.occupied[..] {
+        [occupied] => Ok(occupied),
+        _ => Err(Error::no_answer(
+            Day11::DAY,
+            part,
+            format!(
+                "after {} generations the seats oscillate with period {}, occupied {:?}",
+                settling.pre_period, settling.period, settling.occupied
+            ),
+        )),
+    }
+}
+
+/// People take an empty seat with nobody around, and leave an occupied seat
+/// once `tolerance` of the seats around are occupied.
+fn move_people<'a>(
     seat: &SeatType,
-    coordinates: Coordinates,
-) -> Option<SeatType> {
+    around: impl Iterator<Item = &'a SeatType>,
+    tolerance: usize,
+) -> SeatType {
+    let occupied = around.filter(|s| matches!(s, SeatType::Occupied)).count();
     match seat {
-        SeatType::Occupied => {
-            if iter_visible(seats_map, coordinates)
-                .filter(|adja| matches!(adja, SeatType::Occupied))
-                .count()
-                >= 5
-            {
-                Some(SeatType::Empty)
-            } else {
-                None
-            }
-        }
-        SeatType::Empty => {
-            if iter_visible(seats_map, coordinates)
-                .filter(|adja| matches!(adja, SeatType::Occupied))
-                .count()
-                == 0
-            {
-                Some(SeatType::Occupied)
-            } else {
-                None
-            }
-        }
-        SeatType::Floor => None,
+        SeatType::Empty if occupied == 0 => SeatType::Occupied,
+        SeatType::Occupied if occupied >= tolerance => SeatType::Empty,
+        _ => seat.clone(),
     }
 }
 
-static DIRECTIONS: [Coordinates; 8] = [
-    (-1, -1),
-    (-1, 0),
-    (-1, 1),
-    (0, 1),
-    (1, 1),
-    (1, 0),
-    (1, -1),
-    (0, -1),
-];
-
-fn get_seat(seats_map: &SeatsMap, coordinates: Coordinates) -> Option<&SeatType> {
-    seats_map
-        .get(coordinates.1 as usize)
-        .and_then(|line| line.get(coordinates.0 as usize))
+/// People look at the seats next to theirs.
+pub struct Adjacent {
+    pub tolerance: usize,
 }
 
-fn iter_adjacents<'a>(
-    seats_map: &'a SeatsMap,
-    coordinates: Coordinates,
-) -> impl Iterator<Item = &'a SeatType> + 'a {
-    DIRECTIONS
-        .iter()
-        .filter_map(move |(x, y)| get_seat(seats_map, (coordinates.0 + x, coordinates.1 + y)))
+impl Rule for Adjacent {
+    type Cell = SeatType;
+
+    fn neighbourhood(&self, seats_map: &SeatsMap, point: Point) -> Vec<Point> {
+        iter_adjacents(seats_map, point).map(|(p, _)| p).collect()
+    }
+
+    fn transition<'a>(
+        &self,
+        seat: &SeatType,
+        around: impl Iterator<Item = &'a SeatType>,
+    ) -> SeatType {
+        move_people(seat, around, self.tolerance)
+    }
 }
 
-fn iter_visible<'a>(
-    seats_map: &'a SeatsMap,
-    coordinates: Coordinates,
-) -> impl Iterator<Item = &'a SeatType> + 'a {
-    fn find_visible(
-        seats_map: &SeatsMap,
-        direction: Coordinates,
-        target: Coordinates,
-    ) -> Option<&SeatType> {
-        match get_seat(&seats_map, target) {
-            Some(seat) => match seat {
-                SeatType::Floor => find_visible(
-                    seats_map,
-                    direction,
-                    (target.0 + direction.0, target.1 + direction.1),
-                ),
-                _ => Some(seat),
-            },
-            None => None,
-        }
-    };
+/// People look at the first seat they see in each direction.
+pub struct Visible {
+    pub tolerance: usize,
+}
 
-    DIRECTIONS.iter().filter_map(move |(x, y)| {
-        find_visible(seats_map, (*x, *y), (coordinates.0 + x, coordinates.1 + y))
+impl Rule for Visible {
+    type Cell = SeatType;
+
+    fn neighbourhood(&self, seats_map: &SeatsMap, point: Point) -> Vec<Point> {
+        iter_visible(seats_map, point).map(|(p, _)| p).collect()
+    }
+
+    fn transition<'a>(
+        &self,
+        seat: &SeatType,
+        around: impl Iterator<Item = &'a SeatType>,
+    ) -> SeatType {
+        move_people(seat, around, self.tolerance)
+    }
+}
+
+fn iter_adjacents(
+    seats_map: &SeatsMap,
+    coordinates: Point,
+) -> impl Iterator<Item = (Point, &SeatType)> {
+    seats_map.neighbours8(coordinates)
+}
+
+fn iter_visible(
+    seats_map: &SeatsMap,
+    coordinates: Point,
+) -> impl Iterator<Item = (Point, &SeatType)> {
+    DIRECTIONS.iter().filter_map(move |&direction| {
+        seats_map
+            .ray(coordinates, direction)
+            .find(|(_, seat)| !matches!(seat, SeatType::Floor))
     })
 }
 
@@ -213,13 +209,17 @@ L.#.#..#..
 #L######L#
 #.LL###L.L
 #.#L###.##";
-    let parsed = parse(data);
+    let parsed = Day11::parse(data).unwrap();
     assert_eq!(
-        iter_adjacents(&parsed, (0, 0)).collect::<Vec<&SeatType>>(),
+        iter_adjacents(&parsed, (0, 0))
+            .map(|(_, s)| s)
+            .collect::<Vec<&SeatType>>(),
         vec![&SeatType::Occupied, &SeatType::Empty, &SeatType::Floor]
     );
     assert_eq!(
-        iter_adjacents(&parsed, (5, 5)).collect::<Vec<&SeatType>>(),
+        iter_adjacents(&parsed, (5, 5))
+            .map(|(_, s)| s)
+            .collect::<Vec<&SeatType>>(),
         vec![
             &SeatType::Floor,
             &SeatType::Occupied,
@@ -243,23 +243,53 @@ fn test_visible() {
 .........
 #........
 ...#.....";
-    let parsed = parse(data);
+    let parsed = Day11::parse(data).unwrap();
     assert_eq!(
-        iter_visible(&parsed, (0, 0)).collect::<Vec<&SeatType>>(),
+        iter_visible(&parsed, (0, 0))
+            .map(|(_, s)| s)
+            .collect::<Vec<&SeatType>>(),
         vec![&SeatType::Occupied, &SeatType::Occupied]
     );
     assert_eq!(
-        iter_visible(&parsed, (3, 4)).collect::<Vec<&SeatType>>(),
+        iter_visible(&parsed, (3, 4))
+            .map(|(_, s)| s)
+            .collect::<Vec<&SeatType>>(),
         vec![&SeatType::Occupied; 8]
     );
 }
 
+#[test]
+fn test_oscillation() {
+    let seats_map = Day11::parse("L.L").unwrap();
+    let restless = Adjacent { tolerance: 0 };
+    assert_eq!(
+        simulate(&seats_map, restless),
+        Settling {
+            pre_period: 0,
+            period: 2,
+            occupied: vec![0, 2]
+        }
+    );
+    assert!(matches!(
+        settle(&seats_map, Adjacent { tolerance: 0 }, Part::One),
+        Err(Error::NoAnswer { .. })
+    ));
+    assert_eq!(
+        simulate(&seats_map, Adjacent { tolerance: 4 }),
+        Settling {
+            pre_period: 1,
+            period: 1,
+            occupied: vec![2]
+        }
+    );
+}
+
 #[test]
 fn test_p1() {
-    assert_eq!(p1(include_str!("../inputs/day11.txt")), 2275);
+    assert_eq!(Day11::solve_p1(&crate::input::load(11).unwrap()), Ok(2275));
 }
 
 #[test]
 fn test_p2() {
-    assert_eq!(p2(include_str!("../inputs/day11.txt")), 2121);
+    assert_eq!(Day11::solve_p2(&crate::input::load(11).unwrap()), Ok(2121));
 }
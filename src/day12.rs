@@ -1,3 +1,7 @@
+use crate::error::Malformed;
+use crate::parse::{number, parse_lines};
+use crate::{Result, Solution};
+
 #[derive(Debug, PartialEq, Eq)]
 struct State {
     x: isize,
@@ -12,7 +16,7 @@ impl State {
 }
 
 #[derive(Debug)]
-enum Move {
+pub enum Move {
     North(isize),
     South(isize),
     Est(isize),
@@ -23,17 +27,24 @@ enum Move {
 }
 
 impl Move {
-    fn from_line(line: &str) -> Self {
-        let val = line[1..].parse::<isize>().unwrap();
+    fn from_line(line: &str) -> std::result::Result<Self, Malformed> {
+        if line.len() < 2 || !line.is_char_boundary(1) {
+            return Err(Malformed::new(1, "expected `{action}{value}`"));
+        }
+        let val = number(line, &line[1..])?;
+        let is_turn = matches!(&line[0..1], "L" | "R");
+        if is_turn && (val < 0 || val % 90 != 0) {
+            return Err(Malformed::new(2, "turns must be a multiple of 90 degrees"));
+        }
         match &line[0..1] {
-            "N" => Move::North(val),
-            "S" => Move::South(val),
-            "E" => Move::Est(val),
-            "W" => Move::West(val),
-            "L" => Move::Left(val),
-            "R" => Move::Right(val),
-            "F" => Move::Forward(val),
-            _ => unreachable!(),
+            "N" => Ok(Move::North(val)),
+            "S" => Ok(Move::South(val)),
+            "E" => Ok(Move::Est(val)),
+            "W" => Ok(Move::West(val)),
+            "L" => Ok(Move::Left(val)),
+            "R" => Ok(Move::Right(val)),
+            "F" => Ok(Move::Forward(val)),
+            action => Err(Malformed::new(1, format!("unknown action `{}`", action))),
         }
     }
 }
@@ -48,8 +59,8 @@ fn direction(deg: isize) -> (isize, isize) {
     }
 }
 
-fn rules_p1(state: State, m: Move) -> State {
-    match m {
+fn rules_p1(state: State, m: &Move) -> State {
+    match *m {
         Move::North(val) => State::new(state.x, state.y + val, state.dir),
         Move::South(val) => State::new(state.x, state.y - val, state.dir),
         Move::Est(val) => State::new(state.x + val, state.y, state.dir),
@@ -58,124 +69,135 @@ fn rules_p1(state: State, m: Move) -> State {
             let (dx, dy) = direction(state.dir);
             State::new(state.x + dx * val, state.y + dy * val, state.dir)
         }
-        Move::Left(val) => State::new(state.x, state.y, (360 - val + state.dir) % 360),
-        Move::Right(val) => State::new(state.x, state.y, (val + state.dir) % 360),
+        Move::Left(val) => State::new(state.x, state.y, (state.dir - val).rem_euclid(360)),
+        Move::Right(val) => State::new(state.x, state.y, (state.dir + val).rem_euclid(360)),
     }
 }
 
-pub fn p1_fp(input: &str) -> usize {
-    let state = parse(input).fold(State::new(0, 0, 90), rules_p1);
+pub fn p1_fp(moves: &[Move]) -> Result<usize> {
+    let state = moves.iter().fold(State::new(0, 0, 90), rules_p1);
 
-    (state.x.abs() + state.y.abs()) as usize
+    Ok((state.x.abs() + state.y.abs()) as usize)
 }
 
-pub fn p1(input: &str) -> usize {
-    let mut x = 0;
-    let mut y = 0;
-    let mut dx = 1;
-    let mut dy = 0;
-
-    for m in parse(input) {
-        match m {
-            Move::North(val) => y += val,
-            Move::South(val) => y -= val,
-            Move::Est(val) => x += val,
-            Move::West(val) => x -= val,
-            Move::Forward(val) => {
-                x += dx * val;
-                y += dy * val;
-            }
-            Move::Right(val) => {
-                for _ in 0..val / 90 {
-                    let tmp = dx;
-                    dx = dy;
-                    dy = -tmp;
-                }
-            }
-            Move::Left(val) => {
-                for _ in 0..val / 90 {
-                    let tmp = dx;
-                    dx = -dy;
-                    dy = tmp;
-                }
-            }
-        };
-    }
+pub struct Day12;
 
-    (x.abs() + y.abs()) as usize
-}
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+    type Input = Vec<Move>;
+    type Answer = usize;
 
-pub fn p2(input: &str) -> usize {
-    let mut x = 0;
-    let mut y = 0;
-    let mut wx = 10;
-    let mut wy = 1;
-
-    for m in parse(input) {
-        match m {
-            Move::North(val) => wy += val,
-            Move::South(val) => wy -= val,
-            Move::Est(val) => wx += val,
-            Move::West(val) => wx -= val,
-            Move::Forward(val) => {
-                x += wx * val;
-                y += wy * val;
-            }
-            Move::Right(val) => {
-                for _ in 0..val / 90 {
-                    let tmp = wx;
-                    wx = wy;
-                    wy = -tmp;
+    fn parse(input: &str) -> Result<Vec<Move>> {
+        parse_lines(Self::DAY, input, Move::from_line)
+    }
+
+    fn p1(moves: &Vec<Move>) -> Result<usize> {
+        let mut x = 0;
+        let mut y = 0;
+        let mut dx = 1;
+        let mut dy = 0;
+
+        for m in moves {
+            match *m {
+                Move::North(val) => y += val,
+                Move::South(val) => y -= val,
+                Move::Est(val) => x += val,
+                Move::West(val) => x -= val,
+                Move::Forward(val) => {
+                    x += dx * val;
+                    y += dy * val;
+                }
+                Move::Right(val) => {
+                    for _ in 0..val / 90 {
+                        let tmp = dx;
+                        dx = dy;
+                        dy = -tmp;
+                    }
                 }
-            }
-            Move::Left(val) => {
-                for _ in 0..val / 90 {
-                    let tmp = wx;
-                    wx = -wy;
-                    wy = tmp;
+                Move::Left(val) => {
+                    for _ in 0..val / 90 {
+                        let tmp = dx;
+                        dx = -dy;
+                        dy = tmp;
+                    }
                 }
-            }
-        };
+            };
+        }
+
+        Ok((x.abs() + y.abs()) as usize)
     }
 
-    (x.abs() + y.abs()) as usize
-}
+    fn p2(moves: &Vec<Move>) -> Result<usize> {
+        let mut x = 0;
+        let mut y = 0;
+        let mut wx = 10;
+        let mut wy = 1;
+
+        for m in moves {
+            match *m {
+                Move::North(val) => wy += val,
+                Move::South(val) => wy -= val,
+                Move::Est(val) => wx += val,
+                Move::West(val) => wx -= val,
+                Move::Forward(val) => {
+                    x += wx * val;
+                    y += wy * val;
+                }
+                Move::Right(val) => {
+                    for _ in 0..val / 90 {
+                        let tmp = wx;
+                        wx = wy;
+                        wy = -tmp;
+                    }
+                }
+                Move::Left(val) => {
+                    for _ in 0..val / 90 {
+                        let tmp = wx;
+                        wx = -wy;
+                        wy = tmp;
+                    }
+                }
+            };
+        }
 
-fn parse<'a>(input: &'a str) -> impl Iterator<Item = Move> + 'a {
-    input.lines().map(Move::from_line)
+        Ok((x.abs() + y.abs()) as usize)
+    }
 }
 
 #[test]
 fn test_p1() {
-    assert_eq!(p1(include_str!("../inputs/day12.txt")), 845);
+    assert_eq!(Day12::solve_p1(&crate::input::load(12).unwrap()), Ok(845));
 }
 
 #[test]
 fn test_p1_fp() {
-    assert_eq!(p1_fp(include_str!("../inputs/day12.txt")), 845);
+    assert_eq!(
+        p1_fp(&Day12::parse(&crate::input::load(12).unwrap()).unwrap()),
+        Ok(845)
+    );
 }
 
 #[test]
 fn test_moves() {
     assert_eq!(
-        vec![Move::Forward(10)]
-            .into_iter()
+        [Move::Forward(10)]
+            .iter()
             .take(1)
             .fold(State::new(0, 0, 90), rules_p1),
         State::new(10, 0, 90)
     );
 
     assert_eq!(
-        vec![Move::Forward(10), Move::Right(90)]
-            .into_iter()
+        [Move::Forward(10), Move::Right(90)]
+            .iter()
             .take(2)
             .fold(State::new(0, 0, 90), rules_p1),
         State::new(10, 0, 180)
     );
 
     assert_eq!(
-        vec![Move::Forward(10), Move::Right(90), Move::West(50)]
-            .into_iter()
+        [Move::Forward(10), Move::Right(90), Move::West(50)]
+            .iter()
             .take(3)
             .fold(State::new(0, 0, 90), rules_p1),
         State::new(-40, 0, 180)
@@ -184,5 +206,5 @@ fn test_moves() {
 
 #[test]
 fn test_p2() {
-    assert_eq!(p2(include_str!("../inputs/day12.txt")), 27016);
+    assert_eq!(Day12::solve_p2(&crate::input::load(12).unwrap()), Ok(27016));
 }
@@ -1,58 +1,135 @@
+use crate::error::Malformed;
+use crate::parse::number;
+use crate::{Error, Part, Result, Solution};
+
 type BusId = usize;
 
-pub fn p1(input: &str) -> usize {
-    let (timestamp, bus_ids) = parse(input);
+// Out of service buses (the `x` in the input) have id 0.
+pub struct Notes {
+    timestamp: usize,
+    bus_ids: Vec<BusId>,
+}
 
-    let (bus_id, bus_time) = bus_ids
-        .map(|bus_id| {
-            (
-                bus_id,
-                bus_id * (timestamp as f32 / bus_id as f32).ceil() as usize,
-            )
-        })
-        .min_by_key(|a| a.1)
-        .unwrap();
-    bus_id * (bus_time - timestamp)
+fn gcd(a: usize, b: usize) -> usize {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
 }
 
-// I've googled a lot for this
-pub fn p2(input: &str) -> usize {
-    let bus_ids: Vec<(usize, BusId)> = parse2(input).enumerate().collect();
+pub struct Day13;
 
-    let mut timestamp = 0;
-    let mut inc = bus_ids[0].1;
-    for &(i, bus_id) in &bus_ids[1..] {
-        if bus_id == 0 {
-            continue;
-        }
-        // https://en.wikipedia.org/wiki/Chinese_remainder_theorem#Computation
-        while (timestamp + i) % bus_id != 0 {
-            timestamp += inc;
+impl Solution for Day13 {
+    const DAY: u8 = 13;
+    type Input = Notes;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Notes> {
+        let mut lines = input.lines();
+        let timestamp = lines.next().unwrap_or("");
+        let bus_ids = lines.next().unwrap_or("");
+        if let Some(extra) = lines.next() {
+            return Err(Malformed::new(1, "expected only two lines").into_error(
+                Self::DAY,
+                3,
+                extra,
+            ));
         }
-        inc *= bus_id;
+
+        Ok(Notes {
+            timestamp: number(timestamp, timestamp)
+                .map_err(|e| e.into_error(Self::DAY, 1, timestamp))?,
+            bus_ids: bus_ids
+                .split(",")
+                .map(|b| match b {
+                    "x" => Ok(0),
+                    _ => match number(bus_ids, b)? {
+                        0 => Err(Malformed::at(bus_ids, b, "bus ids start from 1")),
+                        id => Ok(id),
+                    },
+                })
+                .collect::<std::result::Result<_, _>>()
+                .map_err(|e| e.into_error(Self::DAY, 2, bus_ids))?,
+        })
     }
-    timestamp
-}
 
-fn parse<'a>(input: &'a str) -> (usize, impl Iterator<Item = BusId> + 'a) {
-    let (timestamp, bus_ids) = input.split_once("\n").unwrap();
-    (
-        timestamp.parse().unwrap(),
-        bus_ids.split(",").filter_map(|b| b.parse::<usize>().ok()),
-    )
-}
+    fn p1(notes: &Notes) -> Result<usize> {
+        let timestamp = notes.timestamp;
 
-fn parse2<'a>(input: &'a str) -> impl Iterator<Item = BusId> + 'a {
-    let (_, bus_ids) = input.split_once("\n").unwrap();
-    bus_ids.split(",").map(|b| b.parse::<usize>().unwrap_or(0))
+        let (bus_id, bus_time) = notes
+            .bus_ids
+            .iter()
+            .filter(|&&bus_id| bus_id != 0)
+            .map(|&bus_id| {
+                (
+                    bus_id,
+                    bus_id * (timestamp as f32 / bus_id as f32).ceil() as usize,
+                )
+            })
+            .min_by_key(|a| a.1)
+            .ok_or_else(|| Error::no_answer(Self::DAY, Part::One, "no bus is in service"))?;
+        Ok(bus_id * (bus_time - timestamp))
+    }
+
+    // I've googled a lot for this
+    fn p2(notes: &Notes) -> Result<usize> {
+        let bus_ids: Vec<(usize, BusId)> = notes.bus_ids.iter().cloned().enumerate().collect();
+
+        let no_answer = |reason: String| Error::no_answer(Self::DAY, Part::Two, reason);
+        let too_large = || no_answer(String::from("the timestamp is too large"));
+
+        let mut timestamp: usize = 0;
+        let mut inc = 1;
+        for &(i, bus_id) in &bus_ids {
+            if bus_id == 0 {
+                continue;
+            }
+            // https://en.wikipedia.org/wiki/Chinese_remainder_theorem#Computation
+            // Timestamps repeat modulo bus_id after bus_id steps at most, so
+            // there is no solution if none of them fits, as with ids that
+            // aren't coprime.
+            let mut steps = 0;
+            while !(timestamp % bus_id + i % bus_id).is_multiple_of(bus_id) {
+                steps += 1;
+                if steps == bus_id {
+                    return Err(no_answer(format!(
+                        "no timestamp has bus {} leave {} minutes after the first",
+                        bus_id, i
+                    )));
+                }
+                timestamp = timestamp.checked_add(inc).ok_or_else(too_large)?;
+            }
+            inc = (inc / gcd(inc, bus_id))
+                .checked_mul(bus_id)
+                .ok_or_else(too_large)?;
+        }
+        Ok(timestamp)
+    }
 }
 
 #[test]
 fn test_p1() {
-    assert_eq!(p1(include_str!("../inputs/day13.txt")), 3606);
+    assert_eq!(Day13::solve_p1(&crate::input::load(13).unwrap()), Ok(3606));
+}
+
+#[test]
+fn test_p2_not_coprime() {
+    assert_eq!(
+        Day13::solve_p2("7\n2,4"),
+        Err(Error::no_answer(
+            13,
+            Part::Two,
+            "no timestamp has bus 4 leave 1 minutes after the first"
+        ))
+    );
+    assert_eq!(Day13::solve_p2("7\n2,x,4"), Ok(2));
+    assert_eq!(Day13::solve_p2("939\n7,13,x,x,59,x,31,19"), Ok(1068781));
 }
 
 #[test]
 fn test_p2() {
-    assert_eq!(p2(include_str!("../inputs/day13.txt")), 379786358533423);
+    assert_eq!(
+        Day13::solve_p2(&crate::input::load(13).unwrap()),
+        Ok(379786358533423)
+    );
 }
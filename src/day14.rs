@@ -1,10 +1,14 @@
+use crate::error::Malformed;
+use crate::parse::{number, parse_lines};
+use crate::{Error, Part, Result, Solution};
 use itertools::{EitherOrBoth::*, Itertools};
-use serde_scan::scan;
 use std::collections::HashMap;
 
+static MASK_LEN: usize = 36;
+
 #[derive(Debug)]
-enum Instruction<'a> {
-    Mask(&'a str),
+pub enum Instruction {
+    Mask(String),
     Malloc(usize, usize),
 }
 
@@ -30,22 +34,29 @@ impl<'a> DecoderChip<'a> {
     }
 }
 
-fn calc_addr_mask(addr: usize, mask: &str) -> String {
+/// `addr` with `mask` applied, `None` if the mask has an unknown bit or is
+/// shorter than the address.
+fn calc_addr_mask(addr: usize, mask: &str) -> Option<String> {
     let bits = format!("{:b}", addr);
 
     mask.chars()
         .rev()
         .zip_longest(bits.chars().rev())
         .map(|pair| match pair {
-            Left(mask_val) => mask_val,
+            Left(mask_val) => Some(mask_val),
             Both(mask_val, bit_val) => match mask_val {
-                '0' => bit_val,
-                '1' | 'X' => mask_val,
-                _ => unreachable!(),
+                '0' => Some(bit_val),
+                '1' | 'X' => Some(mask_val),
+                _ => None,
             },
-            Right(_) => unreachable!(),
+            Right(_) => None,
         })
-        .collect::<String>()
+        .collect::<Option<String>>()
+        .map(|masked| masked.chars().rev().collect())
+}
+
+fn invalid_mask(part: Part, mask: &str) -> Error {
+    Error::no_answer(Day14::DAY, part, format!("invalid mask `{}`", mask))
 }
 
 fn resolve_floating_bits(mask: String) -> Vec<String> {
@@ -61,71 +72,149 @@ fn resolve_floating_bits(mask: String) -> Vec<String> {
     vec![a, b]
 }
 
-fn run_instruction_v2<'a>(chip: DecoderChip<'a>, instruction: Instruction<'a>) -> DecoderChip<'a> {
-    match instruction {
-        Instruction::Mask(raw_mask) => DecoderChip { raw_mask, ..chip },
+fn run_instruction_v2<'a>(
+    chip: DecoderChip<'a>,
+    instruction: &'a Instruction,
+) -> Result<DecoderChip<'a>> {
+    match *instruction {
+        Instruction::Mask(ref raw_mask) => Ok(DecoderChip { raw_mask, ..chip }),
         Instruction::Malloc(addr, val) => {
-            let masked_addr = calc_addr_mask(addr, chip.raw_mask);
+            let raw_mask = chip.raw_mask;
+            let invalid = || invalid_mask(Part::Two, raw_mask);
+            let masked_addr = calc_addr_mask(addr, raw_mask).ok_or_else(invalid)?;
             let mut memory = chip.memory;
 
             for masked_addr in resolve_floating_bits(masked_addr) {
-                let masked_addr = usize::from_str_radix(masked_addr.as_str(), 2).unwrap();
+                let masked_addr =
+                    usize::from_str_radix(masked_addr.as_str(), 2).map_err(|_| invalid())?;
                 memory.insert(masked_addr, val);
             }
 
-            DecoderChip { memory, ..chip }
+            Ok(DecoderChip { memory, ..chip })
         }
     }
 }
 
-fn run_instruction_v1<'a>(chip: DecoderChip<'a>, instruction: Instruction<'a>) -> DecoderChip<'a> {
-    match instruction {
-        Instruction::Mask(mask) => {
-            let and_mask = usize::from_str_radix(&mask.replace("X", "1"), 2).unwrap();
-            let or_mask = usize::from_str_radix(&mask.replace("X", "0"), 2).unwrap();
-            DecoderChip {
-                and_mask,
-                or_mask,
+fn run_instruction_v1<'a>(
+    chip: DecoderChip<'a>,
+    instruction: &'a Instruction,
+) -> Result<DecoderChip<'a>> {
+    match *instruction {
+        Instruction::Mask(ref mask) => {
+            let bits = |x| {
+                usize::from_str_radix(&mask.replace("X", x), 2)
+                    .map_err(|_| invalid_mask(Part::One, mask))
+            };
+            Ok(DecoderChip {
+                and_mask: bits("1")?,
+                or_mask: bits("0")?,
                 ..chip
-            }
+            })
         }
         Instruction::Malloc(addr, val) => {
             let mut memory = chip.memory;
-            memory.insert(addr, val | chip.or_mask & chip.and_mask);
-            DecoderChip { memory, ..chip }
+            memory.insert(addr, (val | chip.or_mask) & chip.and_mask);
+            Ok(DecoderChip { memory, ..chip })
         }
     }
 }
 
-pub fn p1(input: &str) -> usize {
-    let decoder_chip = parse(input).fold(DecoderChip::new(), run_instruction_v1);
+pub struct Day14;
 
-    decoder_chip.memory.values().sum()
-}
+impl Solution for Day14 {
+    const DAY: u8 = 14;
+    type Input = Vec<Instruction>;
+    type Answer = usize;
 
-pub fn p2(input: &str) -> usize {
-    let decoder_chip = parse(input).fold(DecoderChip::new(), run_instruction_v2);
+    fn parse(input: &str) -> Result<Vec<Instruction>> {
+        let instructions = parse_lines(Self::DAY, input, parse_instruction)?;
+        // One instruction per line: writes before any mask start at line 1.
+        if let Some(Instruction::Malloc(..)) = instructions.first() {
+            let line = input.lines().next().unwrap_or("");
+            let error = Malformed::new(1, "expected a mask before the first write");
+            return Err(error.into_error(Self::DAY, 1, line));
+        }
+        Ok(instructions)
+    }
 
-    decoder_chip.memory.values().sum()
-}
+    fn p1(instructions: &Vec<Instruction>) -> Result<usize> {
+        let decoder_chip = instructions
+            .iter()
+            .try_fold(DecoderChip::new(), run_instruction_v1)?;
+
+        Ok(decoder_chip.memory.values().sum())
+    }
+
+    fn p2(instructions: &Vec<Instruction>) -> Result<usize> {
+        let decoder_chip = instructions
+            .iter()
+            .try_fold(DecoderChip::new(), run_instruction_v2)?;
 
-fn parse<'a>(input: &'a str) -> impl Iterator<Item = Instruction> + 'a {
-    input.lines().map(parse_instruction)
+        Ok(decoder_chip.memory.values().sum())
+    }
 }
 
-fn parse_instruction(line: &str) -> Instruction {
-    if line.starts_with("mask") {
-        let bits = scan!("mask = {}" <- line).unwrap();
-        return Instruction::Mask(bits);
+fn parse_instruction(line: &str) -> std::result::Result<Instruction, Malformed> {
+    if let Some(bits) = line.strip_prefix("mask = ") {
+        if bits.len() != MASK_LEN {
+            return Err(Malformed::at(
+                line,
+                bits,
+                format!("expected a mask of {} bits", MASK_LEN),
+            ));
+        }
+        if let Some((i, c)) = bits.char_indices().find(|(_, c)| !"01X".contains(*c)) {
+            return Err(Malformed::at(
+                line,
+                &bits[i..],
+                format!("unknown mask bit `{}`", c),
+            ));
+        }
+        Ok(Instruction::Mask(bits.to_string()))
+    } else if let Some(malloc) = line.strip_prefix("mem[") {
+        let (addr, val) = malloc
+            .split_once("] = ")
+            .ok_or_else(|| Malformed::at(line, malloc, "expected `{address}] = {value}`"))?;
+        let addr = number(line, addr)?;
+        if addr >> MASK_LEN != 0 {
+            return Err(Malformed::at(
+                line,
+                malloc,
+                format!("address doesn't fit in {} bits", MASK_LEN),
+            ));
+        }
+        Ok(Instruction::Malloc(addr, number(line, val)?))
     } else {
-        let (addr, val) = scan!("mem[{}] = {}" <- line).unwrap();
-        return Instruction::Malloc(addr, val);
+        Err(Malformed::new(
+            1,
+            "expected `mask = ...` or `mem[...] = ...`",
+        ))
     }
 }
 
 #[test]
 fn test_p1() {
-    assert_eq!(p1(include_str!("../inputs/day14.txt")), 12610010960049);
+    assert_eq!(
+        Day14::solve_p1(&crate::input::load(14).unwrap()),
+        Ok(12610010960049)
+    );
+}
+
+#[test]
+fn test_missing_mask() {
+    let error = Day14::parse("mem[1] = 2\nmask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX")
+        .err()
+        .unwrap();
+    assert!(error
+        .to_string()
+        .contains("line 1, column 1: expected a mask before the first write"));
+    assert_eq!(Day14::parse("").map(|i| i.len()), Ok(0));
+
+    let chip = DecoderChip::new();
+    assert!(run_instruction_v2(chip, &Instruction::Malloc(1, 2)).is_err());
+    assert_eq!(calc_addr_mask(5, "0X1"), Some(String::from("1X1")));
+    assert_eq!(calc_addr_mask(5, "01"), None);
+    assert_eq!(calc_addr_mask(1, "2"), None);
 }
 
 #[test]
@@ -136,5 +225,8 @@ fn test_resolve_floating_bits() {
 
 #[test]
 fn test_p2() {
-    assert_eq!(p2(include_str!("../inputs/day14.txt")), 3608464522781);
+    assert_eq!(
+        Day14::solve_p2(&crate::input::load(14).unwrap()),
+        Ok(3608464522781)
+    );
 }
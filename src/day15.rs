@@ -1,22 +1,43 @@
+use crate::error::Malformed;
+use crate::parse::{number, parse_lines};
+use crate::{Result, Solution};
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 
 // Memo maps the number with the last turn it was spoken.
 type Memo = HashMap<usize, usize>;
 
-pub fn p1(input: &str) -> usize {
-    game(parse(input)).nth(2019).unwrap()
-}
+pub struct Day15;
 
-pub fn p2(input: &str) -> usize {
-    game(parse(input)).nth(29999999).unwrap()
-}
+impl Solution for Day15 {
+    const DAY: u8 = 15;
+    type Input = Vec<usize>;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Vec<usize>> {
+        let lines: Vec<Vec<usize>> = parse_lines(Self::DAY, input, |line| {
+            line.split(",").map(|n| number(line, n)).collect()
+        })?;
+        match lines.as_slice() {
+            [starting_numbers] => Ok(starting_numbers.clone()),
+            [] => {
+                Err(Malformed::new(1, "expected the starting numbers").into_error(Self::DAY, 1, ""))
+            }
+            _ => Err(Malformed::new(1, "expected a single line").into_error(
+                Self::DAY,
+                2,
+                input.lines().nth(1).unwrap_or(""),
+            )),
+        }
+    }
+
+    fn p1(starting_numbers: &Vec<usize>) -> Result<usize> {
+        Ok(game(starting_numbers.clone()).nth(2019).unwrap())
+    }
 
-fn parse(input: &str) -> Vec<usize> {
-    input
-        .split(",")
-        .map(|n| n.parse::<usize>().unwrap())
-        .collect()
+    fn p2(starting_numbers: &Vec<usize>) -> Result<usize> {
+        Ok(game(starting_numbers.clone()).nth(29999999).unwrap())
+    }
 }
 
 fn game<'a>(starting_numbers: Vec<usize>) -> impl Iterator<Item = usize> + 'a {
@@ -51,10 +72,13 @@ fn game<'a>(starting_numbers: Vec<usize>) -> impl Iterator<Item = usize> + 'a {
 
 #[test]
 fn test_p1() {
-    assert_eq!(p1(include_str!("../inputs/day15.txt")), 1238);
+    assert_eq!(Day15::solve_p1(&crate::input::load(15).unwrap()), Ok(1238));
 }
 
 #[test]
 fn test_p2() {
-    assert_eq!(p2(include_str!("../inputs/day15.txt")), 3745954);
+    assert_eq!(
+        Day15::solve_p2(&crate::input::load(15).unwrap()),
+        Ok(3745954)
+    );
 }
@@ -0,0 +1,142 @@
+use crate::Part;
+use std::fmt::{self, Display};
+use std::io;
+use std::path::Path;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A line of the puzzle input that can't be parsed. Lines and columns start at 1.
+    Parse {
+        day: u8,
+        line: usize,
+        column: usize,
+        text: String,
+        reason: String,
+    },
+    /// The input is well formed but the puzzle has no answer for it.
+    NoAnswer { day: u8, part: Part, reason: String },
+    /// The puzzle input can't be read or stored.
+    Io { path: String, reason: String },
+    /// A schema file that can't be parsed. Lines and columns start at 1, 0 if
+    /// the parser doesn't know where the problem is.
+    Schema {
+        path: String,
+        line: usize,
+        column: usize,
+        reason: String,
+    },
+    /// Several problems of the input, in order.
+    Many(Vec<Error>),
+}
+
+impl Error {
+    pub fn no_answer(day: u8, part: Part, reason: impl Into<String>) -> Self {
+        Error::NoAnswer {
+            day,
+            part,
+            reason: reason.into(),
+        }
+    }
+
+    /// `errors` as one error, `None` if there are none.
+    pub fn all(mut errors: Vec<Error>) -> Option<Self> {
+        match errors.len() {
+            0 => None,
+            1 => errors.pop(),
+            _ => Some(Error::Many(errors)),
+        }
+    }
+
+    pub fn io(path: impl AsRef<Path>, error: io::Error) -> Self {
+        Error::Io {
+            path: path.as_ref().display().to_string(),
+            reason: error.to_string(),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Parse {
+                day,
+                line,
+                column,
+                text,
+                reason,
+            } => write!(
+                f,
+                "day {}, line {}, column {}: {}\n    {}\n    {:>width$}",
+                day,
+                line,
+                column,
+                reason,
+                text,
+                "^",
+                width = column
+            ),
+            Error::NoAnswer { day, part, reason } => {
+                write!(f, "day {} part {}: no answer, {}", day, part, reason)
+            }
+            Error::Io { path, reason } => write!(f, "{}: {}", path, reason),
+            Error::Schema {
+                path,
+                line: 0,
+                reason,
+                ..
+            } => write!(f, "{}: invalid schema, {}", path, reason),
+            Error::Schema {
+                path,
+                line,
+                column,
+                reason,
+            } => write!(
+                f,
+                "{}, line {}, column {}: invalid schema, {}",
+                path, line, column, reason
+            ),
+            Error::Many(errors) => {
+                let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+                write!(f, "{}", errors.join("\n"))
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// What is wrong with a line, before knowing which line of the input it is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Malformed {
+    column: usize,
+    reason: String,
+}
+
+impl Malformed {
+    pub fn new(column: usize, reason: impl Into<String>) -> Self {
+        Malformed {
+            column,
+            reason: reason.into(),
+        }
+    }
+
+    /// Points at `token`, which must be a slice of `line`.
+    pub fn at(line: &str, token: &str, reason: impl Into<String>) -> Self {
+        let offset = (token.as_ptr() as usize)
+            .saturating_sub(line.as_ptr() as usize)
+            .min(line.len());
+        Self::new(line[..offset].chars().count() + 1, reason)
+    }
+
+    pub fn into_error(self, day: u8, line: usize, text: &str) -> Error {
+        Error::Parse {
+            day,
+            line,
+            column: self.column,
+            text: text.to_string(),
+            reason: self.reason,
+        }
+    }
+}
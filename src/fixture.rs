@@ -0,0 +1,197 @@
+//! Example inputs with their expected answers, stored as `fixtures/dayNN/<name>.txt`.
+//!
+//! A fixture starts with a header giving the expected answer of one or both
+//! parts, followed by `---` and the puzzle input:
+//!
+//! ```text
+//! p1: 514579
+//! p2: 241861950
+//! ---
+//! 1721
+//! 979
+//! ```
+
+use crate::error::Malformed;
+use crate::input::normalise;
+use crate::solution::Part;
+use crate::{Error, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The fixtures of the repository, wherever the tests run from.
+pub static FIXTURES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures");
+static SEPARATOR: &str = "---";
+
+#[derive(Debug)]
+pub struct Fixture {
+    pub path: PathBuf,
+    pub day: u8,
+    pub expected: Vec<(Part, String)>,
+    pub input: String,
+}
+
+/// A part whose answer differs from the one expected by its fixture.
+#[derive(Debug)]
+pub struct Mismatch {
+    pub path: PathBuf,
+    pub part: Part,
+    pub expected: String,
+    pub actual: Result<String>,
+}
+
+impl Fixture {
+    pub fn parse(path: impl Into<PathBuf>, day: u8, content: &str) -> Result<Self> {
+        let content = normalise(content);
+        let mut expected = vec![];
+        let mut lines = content.lines().enumerate();
+
+        loop {
+            let (i, line) =
+                lines.next().ok_or_else(|| {
+                    Malformed::new(1, format!("missing `{}` before the input", SEPARATOR))
+                        .into_error(day, content.lines().count() + 1, "")
+                })?;
+            if line == SEPARATOR {
+                break;
+            }
+            expected.push(parse_answer(line).map_err(|e| e.into_error(day, i + 1, line))?);
+        }
+
+        Ok(Fixture {
+            path: path.into(),
+            day,
+            expected,
+            input: lines.map(|(_, line)| line).collect::<Vec<_>>().join("\n"),
+        })
+    }
+
+    /// Runs the parts with an expected answer, returning the ones that don't match.
+    /// All of them mismatch if no solution of the day is registered.
+    pub fn check(&self) -> Vec<Mismatch> {
+        let day = crate::find_day(self.day);
+
+        self.expected
+            .iter()
+            .filter_map(|(part, expected)| {
+                let actual = match day {
+                    Some(day) => day.solve(*part, &self.input),
+                    None => Err(Error::no_answer(
+                        self.day,
+                        *part,
+                        "no solution for this day",
+                    )),
+                };
+                match actual {
+                    Ok(ref answer) if answer == expected => None,
+                    _ => Some(Mismatch {
+                        path: self.path.clone(),
+                        part: *part,
+                        expected: expected.clone(),
+                        actual,
+                    }),
+                }
+            })
+            .collect()
+    }
+}
+
+fn parse_answer(line: &str) -> std::result::Result<(Part, String), Malformed> {
+    let (part, answer) = line
+        .split_once(":")
+        .ok_or_else(|| Malformed::new(1, "expected `p1: {answer}` or `p2: {answer}`"))?;
+    let part = match part.trim() {
+        "p1" => Part::One,
+        "p2" => Part::Two,
+        _ => return Err(Malformed::at(line, part, "expected `p1` or `p2`")),
+    };
+    Ok((part, answer.trim().to_string()))
+}
+
+/// Loads every fixture under `dir`, sorted by day and name.
+pub fn load_all(dir: impl AsRef<Path>) -> Result<Vec<Fixture>> {
+    let mut fixtures = vec![];
+
+    for day_dir in read_dir(dir.as_ref())? {
+        let day = match day_dir
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix("day"))
+            .and_then(|day| day.parse::<u8>().ok())
+        {
+            Some(day) if day_dir.is_dir() => day,
+            _ => continue,
+        };
+
+        for path in read_dir(&day_dir)? {
+            if path.extension() == Some("txt".as_ref()) {
+                let content = fs::read_to_string(&path).map_err(|e| Error::io(&path, e))?;
+                fixtures.push(Fixture::parse(path, day, &content)?);
+            }
+        }
+    }
+    Ok(fixtures)
+}
+
+fn read_dir(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = fs::read_dir(dir)
+        .and_then(|entries| {
+            entries
+                .map(|entry| entry.map(|e| e.path()))
+                .collect::<std::io::Result<Vec<_>>>()
+        })
+        .map_err(|e| Error::io(dir, e))?;
+    paths.sort();
+    Ok(paths)
+}
+
+#[test]
+fn test_parse() {
+    let fixture = Fixture::parse(
+        "example.txt",
+        1,
+        "p1: 514579\r\np2:241861950\r\n---\r\n1721\r\n979\r\n",
+    )
+    .unwrap();
+    assert_eq!(
+        fixture.expected,
+        vec![
+            (Part::One, String::from("514579")),
+            (Part::Two, String::from("241861950"))
+        ]
+    );
+    assert_eq!(fixture.input, "1721\n979");
+    assert!(Fixture::parse("example.txt", 1, "p3: 1\n---\n1").is_err());
+    assert!(Fixture::parse("example.txt", 1, "p1: 1\n1").is_err());
+}
+
+#[test]
+fn test_unknown_day() {
+    let fixture = Fixture::parse("day25/example.txt", 25, "p1: 1\np2: 2\n---\n1").unwrap();
+    let mismatches = fixture.check();
+    assert_eq!(mismatches.len(), 2);
+    assert_eq!(
+        mismatches[0].actual.as_ref().unwrap_err().to_string(),
+        "day 25 part 1: no answer, no solution for this day"
+    );
+}
+
+#[test]
+fn test_fixtures() {
+    let fixtures = load_all(FIXTURES_DIR).unwrap();
+    assert!(!fixtures.is_empty());
+
+    let mismatches: Vec<String> = fixtures
+        .iter()
+        .flat_map(Fixture::check)
+        .map(|m| {
+            format!(
+                "{} part {}: expected {}, got {:?}",
+                m.path.display(),
+                m.part,
+                m.expected,
+                m.actual
+            )
+        })
+        .collect();
+    assert!(mismatches.is_empty(), "\n{}", mismatches.join("\n"));
+}
@@ -0,0 +1,307 @@
+use crate::error::Malformed;
+use crate::parse::parse_lines;
+use crate::Result;
+use std::fmt::{self, Display};
+
+/// `(x, y)`, with `y` growing downwards.
+pub type Point = (isize, isize);
+
+pub static ORTHOGONAL: [Point; 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+pub static DIRECTIONS: [Point; 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+];
+
+/// What a coordinate outside of the grid refers to, chosen for each axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Addressing {
+    /// Nothing is outside of the grid.
+    Bounded,
+    /// The grid repeats itself.
+    Wrapping,
+    /// Everything outside of the grid is the background.
+    Infinite,
+}
+
+enum Resolved {
+    Inside(usize),
+    Outside,
+}
+
+impl Addressing {
+    fn resolve(self, coordinate: isize, len: usize) -> Option<Resolved> {
+        let inside = coordinate >= 0 && (coordinate as usize) < len;
+        match self {
+            _ if inside => Some(Resolved::Inside(coordinate as usize)),
+            Addressing::Bounded => None,
+            Addressing::Wrapping if len == 0 => None,
+            Addressing::Wrapping => Some(Resolved::Inside(
+                coordinate.rem_euclid(len as isize) as usize
+            )),
+            Addressing::Infinite => Some(Resolved::Outside),
+        }
+    }
+}
+
+/// A rectangle of cells stored row by row in a single buffer.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+    addressing: (Addressing, Addressing),
+    background: Option<T>,
+}
+
+impl<T> Grid<T> {
+    /// A bounded grid, `cells` holding `height` rows of `width` cells.
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(cells.len(), width * height, "wrong number of cells");
+        Grid {
+            width,
+            height,
+            cells,
+            addressing: (Addressing::Bounded, Addressing::Bounded),
+            background: None,
+        }
+    }
+
+    /// Parses one row per line, mapping every character with `cell`. All
+    /// rows must have the same length.
+    pub fn parse<F>(day: u8, input: &str, mut cell: F) -> Result<Self>
+    where
+        F: FnMut(char) -> std::result::Result<T, String>,
+    {
+        let mut width = None;
+        let rows = parse_lines(day, input, |line| {
+            if line.is_empty() {
+                return Err(Malformed::new(1, "empty row"));
+            }
+            let row = line
+                .char_indices()
+                .map(|(i, c)| cell(c).map_err(|reason| Malformed::at(line, &line[i..], reason)))
+                .collect::<std::result::Result<Vec<T>, Malformed>>()?;
+            match width {
+                Some(width) if width != row.len() => Err(Malformed::new(
+                    1,
+                    format!("expected {} cells, found {}", width, row.len()),
+                )),
+                _ => {
+                    width = Some(row.len());
+                    Ok(row)
+                }
+            }
+        })?;
+
+        let height = rows.len();
+        Ok(Self::new(
+            width.unwrap_or(0),
+            height,
+            rows.into_iter().flatten().collect(),
+        ))
+    }
+
+    pub fn with_addressing(mut self, x: Addressing, y: Addressing) -> Self {
+        self.addressing = (x, y);
+        self
+    }
+
+    /// The cell found outside of the grid along `Infinite` axes.
+    pub fn with_background(mut self, background: T) -> Self {
+        self.background = Some(background);
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The cells of the grid, row by row.
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn cells_mut(&mut self) -> &mut [T] {
+        &mut self.cells
+    }
+
+    /// Position in `cells` of the cell at `point`, the background having none.
+    pub fn index_of(&self, point: Point) -> Option<usize> {
+        self.index(point)?
+    }
+
+    fn index(&self, point: Point) -> Option<Option<usize>> {
+        let x = self.addressing.0.resolve(point.0, self.width)?;
+        let y = self.addressing.1.resolve(point.1, self.height)?;
+        match (x, y) {
+            (Resolved::Inside(x), Resolved::Inside(y)) => Some(Some(y * self.width + x)),
+            _ => Some(None),
+        }
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        match self.index(point)? {
+            Some(i) => self.cells.get(i),
+            None => self.background.as_ref(),
+        }
+    }
+
+    /// Only the cells of the grid can be changed, not its background.
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        let i = self.index(point)??;
+        self.cells.get_mut(i)
+    }
+
+    /// Every cell of the grid with its point, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> + '_ {
+        let width = self.width.max(1);
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, cell)| (((i % width) as isize, (i / width) as isize), cell))
+    }
+
+    /// The same grid, with every cell and the background mapped by `f`.
+    pub fn map<U>(&self, mut f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(&mut f).collect(),
+            addressing: self.addressing,
+            background: self.background.as_ref().map(f),
+        }
+    }
+
+    fn neighbours<'a>(
+        &'a self,
+        point: Point,
+        directions: &'static [Point],
+    ) -> impl Iterator<Item = (Point, &'a T)> + 'a {
+        directions.iter().filter_map(move |(dx, dy)| {
+            let neighbour = (point.0 + dx, point.1 + dy);
+            self.get(neighbour).map(|cell| (neighbour, cell))
+        })
+    }
+
+    /// The cells up, right, down and left of `point`.
+    pub fn neighbours4(&self, point: Point) -> impl Iterator<Item = (Point, &T)> + '_ {
+        self.neighbours(point, &ORTHOGONAL)
+    }
+
+    /// The cells around `point`, diagonals included.
+    pub fn neighbours8(&self, point: Point) -> impl Iterator<Item = (Point, &T)> + '_ {
+        self.neighbours(point, &DIRECTIONS)
+    }
+
+    /// The cells met going from `from`, excluded, towards `direction`. Unless
+    /// the grid is bounded on the axes `direction` moves along, it never ends.
+    pub fn ray(&self, from: Point, direction: Point) -> impl Iterator<Item = (Point, &T)> + '_ {
+        let mut point = from;
+        std::iter::from_fn(move || {
+            point = (point.0 + direction.0, point.1 + direction.1);
+            self.get(point).map(|cell| (point, cell))
+        })
+    }
+
+    /// One line per row, every cell drawn with `draw`.
+    pub fn render(&self, mut draw: impl FnMut(&T) -> char) -> String {
+        self.cells
+            .chunks(self.width.max(1))
+            .map(|row| row.iter().map(&mut draw).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, row) in self.cells.chunks(self.width.max(1)).enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+fn parse_digits(input: &str) -> Grid<u32> {
+    Grid::parse(0, input, |c| {
+        c.to_digit(10)
+            .ok_or_else(|| format!("`{}` is not a digit", c))
+    })
+    .unwrap()
+}
+
+#[test]
+fn test_parse() {
+    let grid = parse_digits("123\n456");
+    assert_eq!((grid.width(), grid.height()), (3, 2));
+    assert_eq!(grid.get((2, 1)), Some(&6));
+    assert_eq!(grid.to_string(), "123\n456");
+    assert_eq!(
+        grid.render(|&d| if d % 2 == 0 { '#' } else { '.' }),
+        ".#.\n#.#"
+    );
+
+    let error = Grid::parse(3, "12\n1x", |c| {
+        c.to_digit(10).ok_or_else(|| String::from("nope"))
+    });
+    assert!(matches!(
+        error,
+        Err(crate::Error::Parse {
+            line: 2,
+            column: 2,
+            ..
+        })
+    ));
+    assert!(Grid::parse(3, "12\n1", Ok::<char, String>).is_err());
+}
+
+#[test]
+fn test_addressing() {
+    let grid = parse_digits("123\n456");
+    assert_eq!(grid.get((3, 0)), None);
+    assert_eq!(grid.get((-1, 0)), None);
+
+    let grid = grid.with_addressing(Addressing::Wrapping, Addressing::Bounded);
+    assert_eq!(grid.get((3, 0)), Some(&1));
+    assert_eq!(grid.get((-1, 1)), Some(&6));
+    assert_eq!(grid.get((0, 2)), None);
+
+    let mut grid = grid
+        .with_addressing(Addressing::Infinite, Addressing::Infinite)
+        .with_background(0);
+    assert_eq!(grid.get((-5, 7)), Some(&0));
+    assert_eq!(grid.get_mut((-5, 7)), None);
+    *grid.get_mut((1, 1)).unwrap() = 9;
+    assert_eq!(grid.map(|d| d * 2).get((1, 1)), Some(&18));
+}
+
+#[test]
+fn test_neighbours() {
+    let grid = parse_digits("123\n456\n789");
+    let cells =
+        |it: &mut dyn Iterator<Item = (Point, &u32)>| it.map(|(_, &c)| c).collect::<Vec<_>>();
+    assert_eq!(cells(&mut grid.neighbours4((1, 1))), vec![2, 6, 8, 4]);
+    assert_eq!(cells(&mut grid.neighbours8((0, 0))), vec![4, 5, 2]);
+    assert_eq!(cells(&mut grid.ray((0, 0), (1, 1))), vec![5, 9]);
+    assert_eq!(
+        grid.ray((2, 2), (0, -1)).collect::<Vec<_>>(),
+        vec![((2, 1), &6), ((2, 0), &3)]
+    );
+}
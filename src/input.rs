@@ -0,0 +1,84 @@
+use crate::{Error, Result};
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// Environment variable overriding the default inputs directory.
+pub static INPUTS_VAR: &str = "AOC2020_INPUTS";
+static DEFAULT_DIR: &str = "inputs";
+
+/// Directory caching the puzzle inputs, one `dayNN.txt` file per day.
+pub struct Inputs {
+    dir: PathBuf,
+}
+
+impl Inputs {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Inputs { dir: dir.into() }
+    }
+
+    /// `$AOC2020_INPUTS` when set, `inputs` otherwise.
+    pub fn from_env() -> Self {
+        Self::new(env::var_os(INPUTS_VAR).unwrap_or_else(|| DEFAULT_DIR.into()))
+    }
+
+    pub fn path(&self, day: u8) -> PathBuf {
+        self.dir.join(format!("day{:02}.txt", day))
+    }
+
+    pub fn load(&self, day: u8) -> Result<String> {
+        read_file(self.path(day))
+    }
+
+    /// Stores `input` as the input of `day`, so that later runs find it.
+    pub fn save(&self, day: u8, input: &str) -> Result<()> {
+        let path = self.path(day);
+        fs::create_dir_all(&self.dir)
+            .and_then(|_| fs::write(&path, normalise(input)))
+            .map_err(|e| Error::io(&path, e))
+    }
+}
+
+/// Input of `day` as committed in the repository, whatever `$AOC2020_INPUTS`
+/// says, since the tests check the answers to these inputs.
+pub fn load(day: u8) -> Result<String> {
+    Inputs::new(concat!(env!("CARGO_MANIFEST_DIR"), "/inputs")).load(day)
+}
+
+pub fn read_file(path: impl AsRef<Path>) -> Result<String> {
+    let path = path.as_ref();
+    fs::read_to_string(path)
+        .map(|input| normalise(&input))
+        .map_err(|e| Error::io(path, e))
+}
+
+pub fn read_stdin() -> Result<String> {
+    let mut input = String::new();
+    io::stdin()
+        .read_to_string(&mut input)
+        .map_err(|e| Error::io("<stdin>", e))?;
+    Ok(normalise(&input))
+}
+
+/// Turns CRLF line endings into LF and strips whitespace at the end of every line
+/// and blank lines at the end of the input, so that the days only have to handle `\n`.
+pub fn normalise(input: &str) -> String {
+    let lines: Vec<&str> = input.lines().map(str::trim_end).collect();
+    lines.join("\n").trim_end().to_string()
+}
+
+#[test]
+fn test_normalise() {
+    assert_eq!(
+        normalise("ecl:gry pid:860033327 \r\nbyr:1937\r\n\r\niyr:2013\r\n\r\n"),
+        "ecl:gry pid:860033327\nbyr:1937\n\niyr:2013"
+    );
+    assert_eq!(normalise("0,3,6\n"), "0,3,6");
+}
+
+#[test]
+fn test_load() {
+    assert!(load(4).is_ok());
+    assert!(Inputs::new("/nonexistent").load(4).is_err());
+}
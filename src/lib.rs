@@ -1,5 +1,5 @@
-#![feature(iterator_fold_self, str_split_once)]
-
+pub mod automaton;
+pub mod bench;
 pub mod day01;
 pub mod day02;
 pub mod day03;
@@ -15,3 +15,54 @@ pub mod day12;
 pub mod day13;
 pub mod day14;
 pub mod day15;
+pub mod error;
+pub mod fixture;
+pub mod grid;
+pub mod input;
+pub mod parse;
+pub mod solution;
+pub mod visualise;
+
+pub use error::{Error, Result};
+pub use solution::{Part, Solution};
+
+/// Every solved day, in order.
+pub static DAYS: &[solution::Day] = &[
+    day!(day01::Day01),
+    day!(day02::Day02),
+    day!(day03::Day03),
+    day!(day04::Day04),
+    day!(day05::Day05),
+    day!(day06::Day06),
+    day!(day07::Day07),
+    day!(day08::Day08, "p2_brute_force" => Two day08::p2_brute_force),
+    day!(day09::Day09),
+    day!(day10::Day10),
+    day!(day11::Day11),
+    day!(day12::Day12, "p1_fp" => One day12::p1_fp),
+    day!(day13::Day13),
+    day!(day14::Day14),
+    day!(day15::Day15),
+];
+
+pub fn find_day(day: u8) -> Option<&'static solution::Day> {
+    DAYS.iter().find(|d| d.day == day)
+}
+
+#[test]
+fn test_alternates_agree() {
+    for day in DAYS.iter().filter(|day| day.implementations.len() > 2) {
+        let input = (day.parse)(&input::load(day.day).unwrap()).unwrap();
+        for &part in &[Part::One, Part::Two] {
+            let expected = (day.main(part).run)(input.as_ref());
+            for alternate in day.alternates(part) {
+                assert_eq!(
+                    (alternate.run)(input.as_ref()),
+                    expected,
+                    "{}",
+                    alternate.name
+                );
+            }
+        }
+    }
+}
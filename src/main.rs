@@ -1,6 +1,627 @@
+use aoc2020::bench::{self, Measurement};
+use aoc2020::day04::record::{self, Unit};
+use aoc2020::day04::report;
+use aoc2020::day04::schema::Schema;
+use aoc2020::day04::{Batch, Day04};
+use aoc2020::day07::render::{self, Filter};
+use aoc2020::day07::{self, Day07};
+use aoc2020::day08::trace::{self, Trace};
+use aoc2020::day08::{analysis, asm};
+use aoc2020::day08::{debugger, Day08};
+use aoc2020::day11::{self, Adjacent, Visible};
+use aoc2020::input::{self, Inputs};
+use aoc2020::solution::{Day, Solution};
+use aoc2020::visualise::{self, Export, Format};
+use aoc2020::Part;
+use std::env;
+use std::io;
+use std::process;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+static USAGE: &str = "Usage:
+    aoc2020 <day> [1|2|both] [--input <path>] [--save]
+    aoc2020 all
+    aoc2020 bench [<day>] [--runs <n>] [--json]
+    aoc2020 list
+    aoc2020 validate [--schema <path>] [--report [--json]] [--input <path>]
+    aoc2020 records [csv|json] [--schema <path>] [--unit cm|in]
+                    [--input <path>]
+    aoc2020 bags [dot|mermaid] [--bag <colour>] [--ancestors|--descendants]
+                 [--input <path>]
+    aoc2020 debug [--input <path>]
+    aoc2020 trace [dot|json] [--input <path>]
+    aoc2020 asm|disasm [--input <path>]
+    aoc2020 analyse [--input <path>]
+    aoc2020 seats [1|2] [--input <path>] [--delay <ms>]
+                  [--export <dir>] [--format ppm|png|gif] [--scale <n>]
+
+Puzzle inputs are read from <dir>/dayNN.txt, where <dir> is given by
+--inputs <dir>, the AOC2020_INPUTS variable or defaults to inputs.
+--input reads another file instead, `--input -` reads stdin, and
+--save stores that input in <dir> for the next runs.
+
+validate checks the day 4 passports, or any documents following the TOML
+or JSON schema given by --schema. --report tells why each rejected
+document is, in text or JSON.
+records exports the valid day 4 passports in CSV or JSON, with every
+height converted to --unit if given, and lists the valid documents that
+aren't passports on stderr.
+bags draws the day 7 rules in Graphviz DOT or Mermaid, highlighting the
+--bag colour, shiny gold by default, and keeping only the bags containing
+it or inside it with --ancestors or --descendants.
+debug steps through the day 8 program, reading commands from stdin.
+trace prints the control-flow graph of the day 8 program in Graphviz DOT,
+or the pc and acc of every step in JSON.
+asm turns day 8 assembly, with labels and comments, into a puzzle program;
+disasm does the opposite.
+analyse lists the dead code, out of bounds jumps and inescapable loops
+of the day 8 program, without running it.
+seats animates the day 11 seating with the rules of the given part, or
+with --export writes its generations as images in <dir>.";
+
+enum Command {
+    Day {
+        day: &'static Day,
+        parts: Vec<Part>,
+        input: Option<String>,
+        save: bool,
+    },
+    All,
+    Bench {
+        day: Option<&'static Day>,
+        options: bench::Options,
+        json: bool,
+    },
+    List,
+    Validate {
+        schema: Option<String>,
+        report: bool,
+        json: bool,
+        input: Option<String>,
+    },
+    Records {
+        json: bool,
+        schema: Option<String>,
+        unit: Option<Unit>,
+        input: Option<String>,
+    },
+    Bags {
+        mermaid: bool,
+        bag: String,
+        scope: Scope,
+        input: Option<String>,
+    },
+    Debug {
+        input: Option<String>,
+    },
+    Trace {
+        json: bool,
+        input: Option<String>,
+    },
+    Assemble {
+        input: Option<String>,
+    },
+    Disassemble {
+        input: Option<String>,
+    },
+    Analyse {
+        input: Option<String>,
+    },
+    Seats {
+        part: Part,
+        input: Option<String>,
+        delay: Duration,
+        export: Option<(String, Export)>,
+    },
+}
+
+/// Which bags `bags` draws, owning nothing unlike [`Filter`].
+#[derive(Clone, Copy)]
+enum Scope {
+    All,
+    Ancestors,
+    Descendants,
+}
+
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let result = parse_args(&args).and_then(|(command, inputs)| match command {
+        Command::Day {
+            day,
+            parts,
+            input,
+            save,
+        } => run_day(day, &parts, &inputs, input.as_deref(), save),
+        Command::All => run_all(&inputs),
+        Command::Bench { day, options, json } => run_bench(day, &inputs, options, json),
+        Command::List => {
+            list();
+            Ok(())
+        }
+        Command::Validate {
+            schema,
+            report,
+            json,
+            input,
+        } => run_validate(&inputs, input.as_deref(), schema.as_deref(), report, json),
+        Command::Records {
+            json,
+            schema,
+            unit,
+            input,
+        } => run_records(&inputs, input.as_deref(), json, schema.as_deref(), unit),
+        Command::Bags {
+            mermaid,
+            bag,
+            scope,
+            input,
+        } => run_bags(&inputs, input.as_deref(), mermaid, &bag, scope),
+        Command::Debug { input } => run_debugger(&inputs, input.as_deref()),
+        Command::Trace { json, input } => run_trace(&inputs, input.as_deref(), json),
+        Command::Assemble { input } => run_assemble(&inputs, input.as_deref()),
+        Command::Disassemble { input } => run_disassemble(&inputs, input.as_deref()),
+        Command::Analyse { input } => run_analyse(&inputs, input.as_deref()),
+        Command::Seats {
+            part,
+            input,
+            delay,
+            export,
+        } => run_seats(part, &inputs, input.as_deref(), delay, export),
+    });
+
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<(Command, Inputs), String> {
+    let mut positional = vec![];
+    let mut input = None;
+    let mut inputs = Inputs::from_env();
+    let mut save = false;
+    let mut runs = None;
+    let mut json = false;
+    let mut delay = Duration::from_millis(200);
+    let mut export_dir = None;
+    let mut format = Format::Gif;
+    let mut scale = 4;
+    let mut bag = String::from(day07::MY_BAG);
+    let mut scope = Scope::All;
+    let mut schema = None;
+    let mut report = false;
+    let mut unit = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            "--input" => input = Some(args.next().ok_or("--input needs a path")?.clone()),
+            "--inputs" => inputs = Inputs::new(args.next().ok_or("--inputs needs a directory")?),
+            "--save" => save = true,
+            "--runs" => runs = Some(flag_value("--runs", args.next())?),
+            "--json" => json = true,
+            "--report" => report = true,
+            "--unit" => unit = Some(flag_value("--unit", args.next())?),
+            "--delay" => delay = Duration::from_millis(flag_value("--delay", args.next())?),
+            "--export" => {
+                export_dir = Some(args.next().ok_or("--export needs a directory")?.clone())
+            }
+            "--format" => format = flag_value("--format", args.next())?,
+            "--scale" => scale = flag_value("--scale", args.next())?,
+            "--schema" => schema = Some(args.next().ok_or("--schema needs a path")?.clone()),
+            "--bag" => bag = args.next().ok_or("--bag needs a colour")?.clone(),
+            "--ancestors" => scope = Scope::Ancestors,
+            "--descendants" => scope = Scope::Descendants,
+            _ => positional.push(arg.as_str()),
+        }
+    }
+
+    let command = match positional.as_slice() {
+        ["all"] => Command::All,
+        ["list"] => Command::List,
+        ["bench", ..] if runs == Some(0) => return Err(String::from("--runs must be at least 1")),
+        ["bench", days @ ..] if days.len() <= 1 => Command::Bench {
+            day: days.first().map(|day| find_day(day)).transpose()?,
+            options: runs.map_or_else(bench::Options::default, bench::Options::runs),
+            json,
+        },
+        ["validate"] => Command::Validate {
+            schema,
+            report,
+            json,
+            input,
+        },
+        ["records", format @ ..] if format.len() <= 1 => Command::Records {
+            schema,
+            json: match format.first().copied().unwrap_or("csv") {
+                "csv" => false,
+                "json" => true,
+                format => return Err(format!("unknown format {}, expected csv or json", format)),
+            },
+            unit,
+            input,
+        },
+        ["bags", format @ ..] if format.len() <= 1 => Command::Bags {
+            mermaid: match format.first().copied().unwrap_or("dot") {
+                "dot" => false,
+                "mermaid" => true,
+                format => {
+                    return Err(format!(
+                        "unknown format {}, expected dot or mermaid",
+                        format
+                    ))
+                }
+            },
+            bag,
+            scope,
+            input,
+        },
+        ["debug"] if input.as_deref() == Some("-") => {
+            return Err(String::from(
+                "debug reads its commands from stdin, not the program",
+            ))
+        }
+        ["debug"] => Command::Debug { input },
+        ["asm"] => Command::Assemble { input },
+        ["disasm"] => Command::Disassemble { input },
+        ["analyse"] => Command::Analyse { input },
+        ["trace", format @ ..] if format.len() <= 1 => Command::Trace {
+            json: match format.first().copied().unwrap_or("dot") {
+                "dot" => false,
+                "json" => true,
+                format => return Err(format!("unknown format {}, expected dot or json", format)),
+            },
+            input,
+        },
+        ["seats", ..] if scale == 0 => return Err(String::from("--scale must be at least 1")),
+        ["seats", parts @ ..] if parts.len() <= 1 => Command::Seats {
+            part: match parts.first().copied().unwrap_or("1") {
+                "1" => Part::One,
+                "2" => Part::Two,
+                part => return Err(format!("unknown part {}, expected 1 or 2", part)),
+            },
+            input,
+            delay,
+            export: export_dir.map(|dir| {
+                let export = Export {
+                    format,
+                    scale,
+                    delay,
+                };
+                (dir, export)
+            }),
+        },
+        [day] | [day, _] => {
+            let day = find_day(day)?;
+            let parts = match positional.get(1).copied().unwrap_or("both") {
+                "1" => vec![Part::One],
+                "2" => vec![Part::Two],
+                "both" => vec![Part::One, Part::Two],
+                part => return Err(format!("unknown part {}, expected 1, 2 or both", part)),
+            };
+            if save && input.is_none() {
+                return Err(String::from("--save needs --input"));
+            }
+            Command::Day {
+                day,
+                parts,
+                input,
+                save,
+            }
+        }
+        _ => return Err(USAGE.to_string()),
+    };
+    Ok((command, inputs))
+}
+
+fn flag_value<T: FromStr>(flag: &str, value: Option<&String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{} needs a value", flag))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value {} for {}", value, flag))
+}
+
+fn find_day(day: &str) -> Result<&'static Day, String> {
+    day.parse::<u8>()
+        .ok()
+        .and_then(aoc2020::find_day)
+        .ok_or_else(|| format!("no solution for day {}", day))
+}
+
+fn read_input(inputs: &Inputs, day: u8, path: Option<&str>) -> aoc2020::Result<String> {
+    match path {
+        Some("-") => input::read_stdin(),
+        Some(path) => input::read_file(path),
+        None => inputs.load(day),
+    }
+}
+
+fn timed_solve(day: &Day, part: Part, input: &str) -> (aoc2020::Result<String>, Duration) {
+    let start = Instant::now();
+    let answer = day.solve(part, input);
+    (answer, start.elapsed())
+}
+
+fn run_day(
+    day: &Day,
+    parts: &[Part],
+    inputs: &Inputs,
+    path: Option<&str>,
+    save: bool,
+) -> Result<(), String> {
+    let input = read_input(inputs, day.day, path).map_err(|e| e.to_string())?;
+    if save {
+        inputs.save(day.day, &input).map_err(|e| e.to_string())?;
+    }
+
+    for &part in parts {
+        let (answer, elapsed) = timed_solve(day, part, &input);
+        let answer = answer.map_err(|e| e.to_string())?;
+        println!(
+            "Day {:02} part {}: {} ({:.2?})",
+            day.day, part, answer, elapsed
+        );
+    }
+    Ok(())
+}
+
+fn run_all(inputs: &Inputs) -> Result<(), String> {
+    let mut total = Duration::default();
+    let mut errors = vec![];
+
     println!(
-        "Solution: {}",
-        aoc2020::day15::p2(include_str!("../inputs/day15.txt"))
+        "{:>3} | {:>20} | {:>20} | {:>12}",
+        "Day", "Part 1", "Part 2", "Time"
     );
+    println!("{:-<3}-+-{:-<20}-+-{:-<20}-+-{:-<12}", "", "", "", "");
+    for day in aoc2020::DAYS {
+        let input = inputs.load(day.day).map_err(|e| e.to_string())?;
+        let (p1, p1_elapsed) = timed_solve(day, Part::One, &input);
+        let (p2, p2_elapsed) = timed_solve(day, Part::Two, &input);
+        let elapsed = p1_elapsed + p2_elapsed;
+        total += elapsed;
+
+        let mut cell = |answer: aoc2020::Result<String>| {
+            answer.unwrap_or_else(|e| {
+                errors.push(e);
+                String::from("error")
+            })
+        };
+        println!(
+            "{:>3} | {:>20} | {:>20} | {:>12}",
+            day.day,
+            cell(p1),
+            cell(p2),
+            format!("{:.2?}", elapsed)
+        );
+    }
+    println!("{:-<3}-+-{:-<20}-+-{:-<20}-+-{:-<12}", "", "", "", "");
+    println!("{:>49} | {:>12}", "Total", format!("{:.2?}", total));
+
+    if errors.is_empty() {
+        return Ok(());
+    }
+    // Both parts fail the same way when the input can't be parsed.
+    errors.dedup();
+    let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+    Err(errors.join("\n"))
+}
+
+fn list() {
+    for day in aoc2020::DAYS {
+        let names: Vec<&str> = day.implementations.iter().map(|i| i.name).collect();
+        println!("Day {:02}: {}", day.day, names.join(", "));
+    }
+}
+
+fn run_bench(
+    day: Option<&Day>,
+    inputs: &Inputs,
+    options: bench::Options,
+    json: bool,
+) -> Result<(), String> {
+    let days = match day {
+        Some(day) => std::slice::from_ref(day),
+        None => aoc2020::DAYS,
+    };
+
+    if !json {
+        println!(
+            "{:>3} | {:<12} | {:>6} | {:>12} | {:>12}",
+            "Day", "Step", "Runs", "Mean", "Std dev"
+        );
+        println!(
+            "{:-<3}-+-{:-<12}-+-{:-<6}-+-{:-<12}-+-{:-<12}",
+            "", "", "", "", ""
+        );
+    }
+
+    let mut measurements: Vec<Measurement> = vec![];
+    for day in days {
+        let input = inputs.load(day.day).map_err(|e| e.to_string())?;
+        let day_measurements = bench::bench_day(day, &input, options).map_err(|e| e.to_string())?;
+        if !json {
+            for m in &day_measurements {
+                println!(
+                    "{:>3} | {:<12} | {:>6} | {:>12} | {:>12}",
+                    m.day,
+                    m.name,
+                    m.stats.runs,
+                    format!("{:.2?}", m.stats.mean),
+                    format!("{:.2?}", m.stats.stddev)
+                );
+            }
+        }
+        measurements.extend(day_measurements);
+    }
+
+    if json {
+        let json = serde_json::to_string_pretty(&measurements).map_err(|e| e.to_string())?;
+        println!("{}", json);
+    }
+    Ok(())
+}
+
+fn run_validate(
+    inputs: &Inputs,
+    path: Option<&str>,
+    schema: Option<&str>,
+    report: bool,
+    json: bool,
+) -> Result<(), String> {
+    let input = read_input(inputs, Day04::DAY, path).map_err(|e| e.to_string())?;
+    let batch = Batch::parse(&input, load_schema(schema)?);
+    if report {
+        let rejected: Vec<_> = batch
+            .reports()
+            .into_iter()
+            .filter(|r| !r.is_valid())
+            .collect();
+        if json {
+            println!("{}", report::to_json(&rejected));
+            return Ok(());
+        }
+        for report in rejected {
+            println!("{}", report);
+        }
+    }
+    println!(
+        "{} documents, {} with every required field, {} valid",
+        batch.len(),
+        batch.complete(),
+        batch.valid()
+    );
+    Ok(())
+}
+
+/// The schema at `path`, the passport one if `None`.
+fn load_schema(path: Option<&str>) -> Result<Schema, String> {
+    match path {
+        Some(path) => Schema::load(path).map_err(|e| e.to_string()),
+        None => Ok(Schema::passport()),
+    }
+}
+
+fn run_records(
+    inputs: &Inputs,
+    path: Option<&str>,
+    json: bool,
+    schema: Option<&str>,
+    unit: Option<Unit>,
+) -> Result<(), String> {
+    let input = read_input(inputs, Day04::DAY, path).map_err(|e| e.to_string())?;
+    let batch = Batch::parse(&input, load_schema(schema)?);
+    let (mut records, failures) = batch.records();
+    for (index, error) in failures {
+        eprintln!("document {}: not a passport, {}", index, error);
+    }
+    if let Some(unit) = unit {
+        for record in &mut records {
+            record.height = record.height.to(unit);
+        }
+    }
+    match json {
+        true => println!("{}", record::to_json(&records)),
+        false => print!("{}", record::to_csv(&records)),
+    }
+    Ok(())
+}
+
+fn run_bags(
+    inputs: &Inputs,
+    path: Option<&str>,
+    mermaid: bool,
+    bag: &str,
+    scope: Scope,
+) -> Result<(), String> {
+    let input = read_input(inputs, Day07::DAY, path).map_err(|e| e.to_string())?;
+    let graph = Day07::parse(&input).map_err(|e| e.to_string())?;
+    if !graph.bags().any(|b| b == bag) {
+        return Err(format!("no rule mentions {} bags", bag));
+    }
+    let filter = match scope {
+        Scope::All => Filter::All,
+        Scope::Ancestors => Filter::Ancestors(bag),
+        Scope::Descendants => Filter::Descendants(bag),
+    };
+    match mermaid {
+        true => print!("{}", render::mermaid(&graph, filter, Some(bag))),
+        false => print!("{}", render::dot(&graph, filter, Some(bag))),
+    }
+    Ok(())
+}
+
+fn run_debugger(inputs: &Inputs, path: Option<&str>) -> Result<(), String> {
+    let input = read_input(inputs, Day08::DAY, path).map_err(|e| e.to_string())?;
+    let program = Day08::parse(&input).map_err(|e| e.to_string())?;
+    let stdin = io::stdin();
+    debugger::repl(&program, stdin.lock(), io::stdout()).map_err(|e| e.to_string())
+}
+
+fn run_trace(inputs: &Inputs, path: Option<&str>, json: bool) -> Result<(), String> {
+    let input = read_input(inputs, Day08::DAY, path).map_err(|e| e.to_string())?;
+    let program = Day08::parse(&input).map_err(|e| e.to_string())?;
+    let trace = Trace::run(&program);
+    match json {
+        true => println!("{}", trace.to_json()),
+        false => print!("{}", trace::dot(&program, &trace)),
+    }
+    Ok(())
+}
+
+fn run_assemble(inputs: &Inputs, path: Option<&str>) -> Result<(), String> {
+    let source = read_input(inputs, Day08::DAY, path).map_err(|e| e.to_string())?;
+    let program = asm::assemble(&source).map_err(|e| e.to_string())?;
+    for instruction in program {
+        println!("{}", instruction);
+    }
+    Ok(())
+}
+
+fn run_disassemble(inputs: &Inputs, path: Option<&str>) -> Result<(), String> {
+    let input = read_input(inputs, Day08::DAY, path).map_err(|e| e.to_string())?;
+    let program = Day08::parse(&input).map_err(|e| e.to_string())?;
+    println!("{}", asm::disassemble(&program));
+    Ok(())
+}
+
+fn run_analyse(inputs: &Inputs, path: Option<&str>) -> Result<(), String> {
+    let input = read_input(inputs, Day08::DAY, path).map_err(|e| e.to_string())?;
+    let program = Day08::parse(&input).map_err(|e| e.to_string())?;
+    let diagnostics = analysis::analyse(&program);
+    if diagnostics.is_empty() {
+        println!("nothing to report");
+    }
+    for diagnostic in diagnostics {
+        println!("{}", diagnostic);
+    }
+    Ok(())
+}
+
+fn run_seats(
+    part: Part,
+    inputs: &Inputs,
+    path: Option<&str>,
+    delay: Duration,
+    export: Option<(String, Export)>,
+) -> Result<(), String> {
+    let input = read_input(inputs, day11::Day11::DAY, path).map_err(|e| e.to_string())?;
+    let seats_map = day11::Day11::parse(&input).map_err(|e| e.to_string())?;
+    let frames = match part {
+        Part::One => day11::generations(&seats_map, Adjacent { tolerance: 4 }),
+        Part::Two => day11::generations(&seats_map, Visible { tolerance: 5 }),
+    };
+
+    match export {
+        Some((dir, export)) => {
+            let files = visualise::export(&frames, &dir, export, day11::colour)
+                .map_err(|e| e.to_string())?;
+            println!("Wrote {} file(s) in {}", files.len(), dir);
+            Ok(())
+        }
+        None => visualise::animate(&frames, delay, day11::style).map_err(|e| e.to_string()),
+    }
 }
@@ -0,0 +1,42 @@
+use crate::error::{Malformed, Result};
+use std::str::FromStr;
+
+/// Parses every line of `input`, failing on the first malformed one.
+pub fn parse_lines<T, F>(day: u8, input: &str, mut parse_line: F) -> Result<Vec<T>>
+where
+    F: FnMut(&str) -> std::result::Result<T, Malformed>,
+{
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| parse_line(line).map_err(|e| e.into_error(day, i + 1, line)))
+        .collect()
+}
+
+/// Lines of `input` with their number, grouped in blocks separated by blank lines.
+pub fn blocks(input: &str) -> Vec<Vec<(usize, &str)>> {
+    let mut blocks = vec![];
+    let mut block = vec![];
+
+    for (i, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            if !block.is_empty() {
+                blocks.push(block);
+                block = vec![];
+            }
+        } else {
+            block.push((i + 1, line));
+        }
+    }
+    if !block.is_empty() {
+        blocks.push(block);
+    }
+    blocks
+}
+
+/// Parses `token`, a slice of `line`, as a number.
+pub fn number<T: FromStr>(line: &str, token: &str) -> std::result::Result<T, Malformed> {
+    token
+        .parse()
+        .map_err(|_| Malformed::at(line, token, format!("`{}` is not a valid number", token)))
+}
@@ -0,0 +1,128 @@
+use crate::Result;
+use std::any::Any;
+use std::fmt::{self, Display};
+
+/// A day's puzzle: the input is parsed once and then handed to both parts.
+pub trait Solution {
+    const DAY: u8;
+    type Input: 'static;
+    type Answer: Display;
+
+    fn parse(input: &str) -> Result<Self::Input>;
+    fn p1(input: &Self::Input) -> Result<Self::Answer>;
+    fn p2(input: &Self::Input) -> Result<Self::Answer>;
+
+    fn solve_p1(input: &str) -> Result<Self::Answer> {
+        Self::p1(&Self::parse(input)?)
+    }
+
+    fn solve_p2(input: &str) -> Result<Self::Answer> {
+        Self::p2(&Self::parse(input)?)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+/// Parsed input of a day, as returned by `Day::parse`.
+pub type ParsedInput = Box<dyn Any>;
+
+/// One way of solving a part, working on the parsed input of its day.
+pub struct Implementation {
+    pub name: &'static str,
+    pub part: Part,
+    pub run: fn(&dyn Any) -> Result<String>,
+}
+
+/// A registry entry: the parser of a day and every implementation of its parts.
+/// The first implementation of each part is the main one, the others are alternates.
+pub struct Day {
+    pub day: u8,
+    pub parse: fn(&str) -> Result<ParsedInput>,
+    pub implementations: &'static [Implementation],
+}
+
+impl Day {
+    pub fn main(&self, part: Part) -> &Implementation {
+        self.implementations
+            .iter()
+            .find(|implementation| implementation.part == part)
+            .expect("every day implements both parts")
+    }
+
+    pub fn alternates(&self, part: Part) -> impl Iterator<Item = &Implementation> {
+        self.implementations
+            .iter()
+            .filter(move |implementation| implementation.part == part)
+            .skip(1)
+    }
+
+    pub fn solve(&self, part: Part, input: &str) -> Result<String> {
+        (self.main(part).run)((self.parse)(input)?.as_ref())
+    }
+}
+
+pub fn parse<S: Solution>(input: &str) -> Result<ParsedInput> {
+    Ok(Box::new(S::parse(input)?))
+}
+
+pub fn p1<S: Solution>(input: &dyn Any) -> Result<String> {
+    S::p1(downcast::<S::Input>(input)).map(|answer| answer.to_string())
+}
+
+pub fn p2<S: Solution>(input: &dyn Any) -> Result<String> {
+    S::p2(downcast::<S::Input>(input)).map(|answer| answer.to_string())
+}
+
+/// Recovers the parsed input of a day, for alternate implementations.
+pub fn downcast<T: 'static>(input: &dyn Any) -> &T {
+    input
+        .downcast_ref()
+        .expect("implementation run on the input of another day")
+}
+
+/// Builds the registry entry of a `Solution`, listing its alternates after
+/// the trait's own parts.
+#[macro_export]
+macro_rules! day {
+    ($solution:ty $(, $name:literal => $part:ident $run:expr)* $(,)?) => {
+        $crate::solution::Day {
+            day: <$solution as $crate::solution::Solution>::DAY,
+            parse: $crate::solution::parse::<$solution>,
+            implementations: &[
+                $crate::solution::Implementation {
+                    name: "p1",
+                    part: $crate::solution::Part::One,
+                    run: $crate::solution::p1::<$solution>,
+                },
+                $crate::solution::Implementation {
+                    name: "p2",
+                    part: $crate::solution::Part::Two,
+                    run: $crate::solution::p2::<$solution>,
+                },
+                $($crate::solution::Implementation {
+                    name: $name,
+                    part: $crate::solution::Part::$part,
+                    run: |input| {
+                        let input = $crate::solution::downcast::<
+                            <$solution as $crate::solution::Solution>::Input,
+                        >(input);
+                        $run(input).map(|answer| answer.to_string())
+                    },
+                },)*
+            ],
+        }
+    };
+}
@@ -0,0 +1,282 @@
+//! Drawing the generations of a [`Grid`] in the terminal or as image files.
+
+use crate::grid::Grid;
+use crate::{Error, Result};
+use std::fmt::Display;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+pub type Rgb = [u8; 3];
+
+/// The grid with every cell drawn as a character in its colour, using ANSI escapes.
+pub fn ansi<T>(grid: &Grid<T>, mut style: impl FnMut(&T) -> (char, Rgb)) -> String {
+    let mut lines = vec![];
+    for row in grid.cells().chunks(grid.width().max(1)) {
+        let mut line = String::new();
+        for cell in row {
+            let (c, [r, g, b]) = style(cell);
+            line += &format!("\x1b[38;2;{};{};{}m{}", r, g, b, c);
+        }
+        lines.push(line + "\x1b[0m");
+    }
+    lines.join("\n")
+}
+
+/// Draws the frames one after the other in place, waiting `delay` between them.
+pub fn animate<T>(
+    frames: &[Grid<T>],
+    delay: Duration,
+    mut style: impl FnMut(&T) -> (char, Rgb),
+) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    for (i, frame) in frames.iter().enumerate() {
+        if i > 0 {
+            thread::sleep(delay);
+        }
+        write!(
+            stdout,
+            "\x1b[2J\x1b[H{}\ngeneration {}/{}\n",
+            ansi(frame, &mut style),
+            i,
+            frames.len() - 1
+        )?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// One `frameNNNN.ppm` file per frame.
+    Ppm,
+    /// One `frameNNNN.png` file per frame.
+    Png,
+    /// A single looping `animation.gif`.
+    Gif,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(format: &str) -> std::result::Result<Self, String> {
+        match format {
+            "ppm" => Ok(Format::Ppm),
+            "png" => Ok(Format::Png),
+            "gif" => Ok(Format::Gif),
+            _ => Err(format!(
+                "unknown format {}, expected ppm, png or gif",
+                format
+            )),
+        }
+    }
+}
+
+/// How frames are turned into images.
+#[derive(Debug, Clone, Copy)]
+pub struct Export {
+    pub format: Format,
+    /// Side in pixels of the square drawn for every cell.
+    pub scale: usize,
+    /// Time between frames of an animation.
+    pub delay: Duration,
+}
+
+/// An image with 3 bytes per pixel, row by row.
+struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    fn draw<T>(grid: &Grid<T>, scale: usize, colour: &mut impl FnMut(&T) -> Rgb) -> Self {
+        let (width, height) = (grid.width() * scale, grid.height() * scale);
+        let mut pixels = Vec::with_capacity(width * height * 3);
+        for row in grid.cells().chunks(grid.width().max(1)) {
+            let line: Vec<u8> = row
+                .iter()
+                .flat_map(|cell| colour(cell).repeat(scale))
+                .collect();
+            for _ in 0..scale {
+                pixels.extend(&line);
+            }
+        }
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    fn ppm(&self) -> Vec<u8> {
+        let mut ppm = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        ppm.extend(&self.pixels);
+        ppm
+    }
+}
+
+fn failure(path: &Path, reason: impl Display) -> Error {
+    Error::Io {
+        path: path.display().to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+/// Writes the frames in `dir`, which is created if needed, returning the files written.
+pub fn export<T>(
+    frames: &[Grid<T>],
+    dir: impl AsRef<Path>,
+    options: Export,
+    mut colour: impl FnMut(&T) -> Rgb,
+) -> Result<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir).map_err(|e| Error::io(dir, e))?;
+    let images = frames
+        .iter()
+        .map(|frame| Image::draw(frame, options.scale, &mut colour));
+
+    match options.format {
+        Format::Ppm => images
+            .enumerate()
+            .map(|(i, image)| {
+                let path = dir.join(format!("frame{:04}.ppm", i));
+                fs::write(&path, image.ppm()).map_err(|e| Error::io(&path, e))?;
+                Ok(path)
+            })
+            .collect(),
+        Format::Png => images
+            .enumerate()
+            .map(|(i, image)| {
+                let path = dir.join(format!("frame{:04}.png", i));
+                write_png(&path, &image)?;
+                Ok(path)
+            })
+            .collect(),
+        Format::Gif => {
+            let path = dir.join("animation.gif");
+            write_gif(&path, images.collect(), options.delay)?;
+            Ok(vec![path])
+        }
+    }
+}
+
+fn write_png(path: &Path, image: &Image) -> Result<()> {
+    let file = File::create(path).map_err(|e| Error::io(path, e))?;
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(file),
+        image.width as u32,
+        image.height as u32,
+    );
+    encoder.set_color(png::ColorType::RGB);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&image.pixels))
+        .map_err(|e| failure(path, e))
+}
+
+fn write_gif(path: &Path, images: Vec<Image>, delay: Duration) -> Result<()> {
+    let (width, height) = images
+        .first()
+        .map_or((0, 0), |image| (image.width, image.height));
+    if width > u16::MAX as usize || height > u16::MAX as usize {
+        return Err(failure(path, "frames too large for a gif"));
+    }
+
+    // The frames share a global palette, the grids having few distinct colours.
+    let mut palette: Vec<Rgb> = vec![];
+    let mut indexed = vec![];
+    for image in &images {
+        let mut frame = Vec::with_capacity(image.width * image.height);
+        for pixel in image.pixels.chunks(3) {
+            let colour = [pixel[0], pixel[1], pixel[2]];
+            let index = match palette.iter().position(|&c| c == colour) {
+                Some(index) => index,
+                None if palette.len() < 256 => {
+                    palette.push(colour);
+                    palette.len() - 1
+                }
+                None => return Err(failure(path, "more than 256 colours")),
+            };
+            frame.push(index as u8);
+        }
+        indexed.push(frame);
+    }
+
+    let file = File::create(path).map_err(|e| Error::io(path, e))?;
+    let flat_palette: Vec<u8> = palette.concat();
+    let mut encoder = gif::Encoder::new(
+        BufWriter::new(file),
+        width as u16,
+        height as u16,
+        &flat_palette,
+    )
+    .map_err(|e| failure(path, e))?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(|e| failure(path, e))?;
+
+    for pixels in indexed {
+        let mut frame = gif::Frame::from_indexed_pixels(width as u16, height as u16, &pixels, None);
+        frame.delay = gif_delay(delay);
+        encoder.write_frame(&frame).map_err(|e| failure(path, e))?;
+    }
+    Ok(())
+}
+
+/// `delay` in hundredths of a second, as GIF frames take it, capped at the
+/// longest they allow.
+fn gif_delay(delay: Duration) -> u16 {
+    (delay.as_millis() / 10).min(u16::MAX.into()) as u16
+}
+
+#[cfg(test)]
+fn checkerboard() -> Grid<bool> {
+    Grid::parse(0, "#.\n.#", |c| Ok(c == '#')).unwrap()
+}
+
+#[cfg(test)]
+fn black_and_white(&cell: &bool) -> Rgb {
+    if cell {
+        [0, 0, 0]
+    } else {
+        [255, 255, 255]
+    }
+}
+
+#[test]
+fn test_ansi() {
+    let drawn = ansi(&checkerboard(), |&cell| {
+        (if cell { '#' } else { '.' }, black_and_white(&cell))
+    });
+    assert_eq!(
+        drawn,
+        "\x1b[38;2;0;0;0m#\x1b[38;2;255;255;255m.\x1b[0m\n\
+         \x1b[38;2;255;255;255m.\x1b[38;2;0;0;0m#\x1b[0m"
+    );
+}
+
+#[test]
+fn test_ppm() {
+    let image = Image::draw(&checkerboard(), 2, &mut black_and_white);
+    assert_eq!((image.width, image.height), (4, 4));
+    let ppm = image.ppm();
+    assert!(ppm.starts_with(b"P6\n4 4\n255\n"));
+    assert_eq!(ppm.len(), 11 + 4 * 4 * 3);
+    assert_eq!(
+        &image.pixels[..12],
+        &[0, 0, 0, 0, 0, 0, 255, 255, 255, 255, 255, 255]
+    );
+    assert_eq!(&image.pixels[12..24], &image.pixels[..12]);
+}
+
+#[test]
+fn test_gif_delay() {
+    assert_eq!(gif_delay(Duration::from_millis(200)), 20);
+    assert_eq!(gif_delay(Duration::from_millis(5)), 0);
+    assert_eq!(gif_delay(Duration::from_secs(1_000_000)), u16::MAX);
+}